@@ -1,6 +1,7 @@
 use base64::{engine::general_purpose, Engine as _};
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum};
 use image::imageops;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::io::{Cursor, Write};
 use std::path::{Path, PathBuf};
@@ -27,12 +28,20 @@ type R<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
 ///
 ///   # Then run this:
 ///   yt-sl --frames frames/ --output slides/ --transcript transcript.json
-#[derive(Parser)]
+#[derive(Parser, Debug)]
 #[command(name = "yt-sl", about = "Dedup frames + vision OCR → markdown report")]
 struct Args {
     /// Directory containing extracted frame images (jpg/png)
-    #[arg(short, long)]
-    frames: String,
+    #[arg(short, long, required_unless_present_any = ["render_only", "build_index"])]
+    frames: Option<String>,
+
+    /// Re-render report.md from a previous run's report.json in DIR (no OCR)
+    #[arg(long, value_name = "DIR", conflicts_with = "frames")]
+    render_only: Option<String>,
+
+    /// Write DIR/index.json: a token -> slides search index over every report.json in DIR
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["frames", "render_only"])]
+    build_index: Option<String>,
 
     /// Output directory for slides + report
     #[arg(short, long, default_value = "./output")]
@@ -42,6 +51,10 @@ struct Args {
     #[arg(long)]
     transcript: Option<String>,
 
+    /// Subtitle file (WebVTT or SRT) used instead of a Whisper transcript
+    #[arg(long, conflicts_with = "transcript")]
+    subtitles: Option<String>,
+
     /// Title for the report
     #[arg(long, default_value = "Untitled")]
     title: String,
@@ -50,6 +63,18 @@ struct Args {
     #[arg(long)]
     url: Option<String>,
 
+    /// Channel/uploader name shown in the report header
+    #[arg(long)]
+    uploader: Option<String>,
+
+    /// Upload date shown in the report header (YYYYMMDD as yt-dlp prints it, or YYYY-MM-DD)
+    #[arg(long, value_name = "DATE")]
+    upload_date: Option<String>,
+
+    /// View count shown in the report header
+    #[arg(long, value_name = "N")]
+    view_count: Option<u64>,
+
     /// Frame extraction interval used (for timestamp calculation)
     #[arg(short, long, default_value = "5")]
     interval: u64,
@@ -58,6 +83,46 @@ struct Args {
     #[arg(short = 'T', long, default_value = "0.90")]
     threshold: f64,
 
+    /// OCR text similarity above which two slides count as duplicates (0.0-1.0)
+    #[arg(long, default_value = "0.6")]
+    text_threshold: f64,
+
+    /// Keep at most N slides, evenly spaced across the video
+    #[arg(long, value_name = "N")]
+    max_slides: Option<usize>,
+
+    /// Downscale stored slide images wider than this (OCR still uses the original frame)
+    #[arg(long, value_name = "PX")]
+    max_slide_width: Option<u32>,
+
+    /// Mark each slide new/changed/unchanged against a previous run's report.json
+    #[arg(long, value_name = "REPORT_JSON")]
+    diff_against: Option<String>,
+
+    /// Write a CSV of each frame's closest accepted frame, similarity and merge decision
+    #[arg(long, value_name = "PATH")]
+    dump_similarity: Option<String>,
+
+    /// Write every frame's number, timestamp, hash and path (before dedup) as JSON
+    #[arg(long, value_name = "PATH")]
+    dump_frames: Option<String>,
+
+    /// Use the sharpest frame of each duplicate group instead of the first (decodes more frames)
+    #[arg(long)]
+    pick_sharpest: bool,
+
+    /// Also require similar color histograms to merge frames (catches recolored slides; decodes frames twice)
+    #[arg(long)]
+    color_aware_dedup: bool,
+
+    /// Stop before OCR if a quick sampling pass finds no static (slide-like) stretches
+    #[arg(long)]
+    require_slides: bool,
+
+    /// Sweep --threshold over the frames, print unique counts and a recommended value, then exit
+    #[arg(long)]
+    autotune: bool,
+
     /// Vision model name
     #[arg(long, default_value = "qwen/qwen3-vl-8b")]
     model: String,
@@ -66,19 +131,387 @@ struct Args {
     #[arg(long, default_value = "http://localhost:1234/v1")]
     vision_api: String,
 
+    /// Prepend YAML front-matter (title, date, source, duration, slide_count) for Jekyll/Hugo
+    #[arg(long)]
+    front_matter: bool,
+
+    /// Language for the report's headings and labels (OCR text is unaffected)
+    #[arg(long, value_enum, default_value = "en")]
+    report_language: ReportLanguage,
+
+    /// Report document format(s), comma-separated; each is written as report.<ext>
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "md")]
+    format: Vec<ReportFormat>,
+
+    /// Base filename of the report document(s), without extension
+    #[arg(long, default_value = "report")]
+    report_name: String,
+
+    /// Inline slide images into report.md as base64 data URLs (self-contained report)
+    #[arg(long)]
+    embed_images: bool,
+
+    /// Exit with an error when no slides are detected (the report is still written)
+    #[arg(long)]
+    fail_on_empty: bool,
+
     /// Max concurrent vision API requests
     #[arg(long, default_value = "4")]
     concurrency: usize,
+
+    /// Threads for CPU-heavy work (hashing, image resizing, encoding); default: all cores
+    #[arg(short = 'j', long, value_name = "N")]
+    jobs: Option<usize>,
+
+    /// Log the command line and resolved settings at startup
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Progress output: text lines on stderr, NDJSON records on stdout, or none
+    #[arg(long, value_enum, default_value = "text")]
+    progress: ProgressMode,
+
+    /// Effective settings vs. defaults, filled in from the parsed matches
+    #[arg(skip)]
+    settings: Vec<Setting>,
+}
+
+/// One invalid setting, kept structured so each can be reported against its flag.
+#[derive(Debug)]
+struct ConfigIssue {
+    field: &'static str,
+    message: String,
+    value: String,
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} (got {})", self.field, self.message, self.value)
+    }
+}
+
+impl Args {
+    fn validate(&self) -> R<()> {
+        self.validate_detailed().map_err(|issues| {
+            issues
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("; ")
+                .into()
+        })
+    }
+
+    /// Every invalid setting at once, rather than failing on the first.
+    fn validate_detailed(&self) -> Result<(), Vec<ConfigIssue>> {
+        let mut issues = Vec::new();
+        for (field, value) in [
+            ("--threshold", self.threshold),
+            ("--text-threshold", self.text_threshold),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                issues.push(ConfigIssue {
+                    field,
+                    message: "must be between 0.0 and 1.0".to_string(),
+                    value: value.to_string(),
+                });
+            }
+        }
+        if self.concurrency == 0 {
+            issues.push(ConfigIssue {
+                field: "--concurrency",
+                message: "must be at least 1".to_string(),
+                value: self.concurrency.to_string(),
+            });
+        }
+        if self.jobs == Some(0) {
+            issues.push(ConfigIssue {
+                field: "--jobs",
+                message: "must be at least 1".to_string(),
+                value: "0".to_string(),
+            });
+        }
+        if self.max_slides == Some(0) {
+            issues.push(ConfigIssue {
+                field: "--max-slides",
+                message: "must keep at least one slide".to_string(),
+                value: "0".to_string(),
+            });
+        }
+        if self.max_slide_width == Some(0) {
+            issues.push(ConfigIssue {
+                field: "--max-slide-width",
+                message: "must be a positive number of pixels".to_string(),
+                value: "0".to_string(),
+            });
+        }
+        if sanitize_report_name(&self.report_name).is_empty() {
+            issues.push(ConfigIssue {
+                field: "--report-name",
+                message: "needs at least one letter, digit, '-' or '_'".to_string(),
+                value: self.report_name.clone(),
+            });
+        }
+        if let Some(frames) = self.frames.as_deref().filter(|_| self.output != "-") {
+            let slides = normalize_path(&Path::new(&self.output).join("slides"));
+            if normalize_path(Path::new(frames)) == slides {
+                issues.push(ConfigIssue {
+                    field: "--frames",
+                    message: format!(
+                        "is the output's slides directory {}, whose stale slide_* images \
+                         a run deletes",
+                        slides.display()
+                    ),
+                    value: frames.to_string(),
+                });
+            }
+        }
+        if self.output == "-" && !self.embed_images {
+            issues.push(ConfigIssue {
+                field: "--output",
+                message: "writes the report to stdout and needs --embed-images".to_string(),
+                value: self.output.clone(),
+            });
+        }
+        if self.output == "-" && self.format.len() > 1 {
+            issues.push(ConfigIssue {
+                field: "--format",
+                message: "takes a single format when writing to stdout (--output -)".to_string(),
+                value: self
+                    .format
+                    .iter()
+                    .map(|f| f.extension())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            });
+        }
+        if self.output == "-" && self.progress == ProgressMode::Ndjson {
+            issues.push(ConfigIssue {
+                field: "--output",
+                message: "writes the report to stdout, which --progress ndjson also uses"
+                    .to_string(),
+                value: self.output.clone(),
+            });
+        }
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+}
+
+/// Absolute `path` with symlinks resolved as far as it exists, and `.`/`..`
+/// applied lexically past that (an output directory usually doesn't exist yet).
+fn normalize_path(path: &Path) -> PathBuf {
+    let abs = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut out = PathBuf::new();
+    for part in abs.components() {
+        match std::fs::canonicalize(out.join(part)) {
+            Ok(real) => out = real,
+            Err(_) => match part {
+                std::path::Component::ParentDir => {
+                    out.pop();
+                }
+                std::path::Component::CurDir => {}
+                _ => out.push(part),
+            },
+        }
+    }
+    out
+}
+
+/// Flags naming inputs, report metadata, diagnostics or console output rather
+/// than processing settings; left out of the report's settings block.
+const NON_SETTINGS: [&str; 18] = [
+    "frames",
+    "render_only",
+    "build_index",
+    "output",
+    "transcript",
+    "subtitles",
+    "title",
+    "url",
+    "uploader",
+    "upload_date",
+    "view_count",
+    "dump_similarity",
+    "dump_frames",
+    "autotune",
+    "verbose",
+    "progress",
+    "help",
+    "version",
+];
+
+/// One effective setting, as listed in the report and report.json.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct Setting {
+    /// The flag, e.g. `--threshold`
+    name: String,
+    value: String,
+    /// The built-in default; `None` for options that are off unless given
+    default: Option<String>,
+    changed: bool,
+}
+
+/// Every processing setting that has a value, set or defaulted, next to clap's
+/// built-in default. Numbers compare by value, so `--threshold 0.9` is
+/// unchanged from `0.90`.
+fn effective_settings(matches: &clap::ArgMatches) -> Vec<Setting> {
+    let mut cmd = Args::command();
+    cmd.build();
+    let joined = |values: Vec<String>| values.join(",");
+    cmd.get_arguments()
+        .filter(|arg| !NON_SETTINGS.contains(&arg.get_id().as_str()))
+        .filter_map(|arg| {
+            let value = joined(
+                matches
+                    .get_raw(arg.get_id().as_str())?
+                    .map(|v| v.to_string_lossy().into_owned())
+                    .collect(),
+            );
+            let default = Some(arg.get_default_values())
+                .filter(|d| !d.is_empty())
+                .map(|d| joined(d.iter().map(|v| v.to_string_lossy().into_owned()).collect()));
+            let changed = default.as_deref().is_none_or(|d| {
+                d != value
+                    && !matches!((d.parse::<f64>(), value.parse::<f64>()), (Ok(a), Ok(b)) if a == b)
+            });
+            Some(Setting {
+                name: format!("--{}", arg.get_long()?),
+                value,
+                default,
+                changed,
+            })
+        })
+        .collect()
+}
+
+/// The settings on one line for the --verbose log: `--name=value`, with the
+/// default alongside for anything changed.
+fn settings_line(settings: &[Setting]) -> String {
+    settings
+        .iter()
+        .map(|s| match (&s.default, s.changed) {
+            (Some(d), true) => format!("{}={} (default {})", s.name, s.value, d),
+            _ => format!("{}={}", s.name, s.value),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 // ── Data types ──────────────────────────────────────────────────────────────
 
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 struct SlideData {
     index: usize,
     timestamp: f64,
     image_path: PathBuf,
     text: String,
     transcript: String,
+    /// Fingerprint of the stored slide image (16 hex digits from the average hash)
+    #[serde(default)]
+    hash: Option<String>,
+    /// Set by `--diff-against`
+    #[serde(default)]
+    change: Option<SlideChange>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+enum SlideChange {
+    New,
+    Changed,
+    Unchanged,
+}
+
+/// Everything a run produced, written next to the markdown as `report.json`.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct ExtractionReport {
+    title: String,
+    url: String,
+    #[serde(default)]
+    duration_secs: f64,
+    #[serde(default)]
+    transcript: String,
+    #[serde(default)]
+    video: VideoInfo,
+    frames_total: usize,
+    frames_unique: usize,
+    #[serde(default)]
+    dedup: DedupStats,
+    slides: Vec<SlideData>,
+    report_path: PathBuf,
+    timings: Timings,
+    ocr_errors: Vec<String>,
+    /// Bytes of the report document(s) plus slides/ (report.json itself excluded)
+    #[serde(default)]
+    output_bytes: u64,
+    /// Effective processing settings, each marked if it differs from the default
+    #[serde(default)]
+    settings: Vec<Setting>,
+    /// File names of the report documents written next to report.json
+    #[serde(default)]
+    documents: Vec<String>,
+}
+
+/// Optional source-video details passed through from yt-dlp by the wrapper.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+struct VideoInfo {
+    uploader: Option<String>,
+    /// YYYY-MM-DD
+    upload_date: Option<String>,
+    view_count: Option<u64>,
+}
+
+impl VideoInfo {
+    fn from_args(args: &Args) -> Self {
+        VideoInfo {
+            uploader: args.uploader.clone().filter(|u| !u.is_empty()),
+            upload_date: args.upload_date.as_deref().map(format_upload_date),
+            view_count: args.view_count,
+        }
+    }
+}
+
+/// yt-dlp's `20240115` as `2024-01-15`; anything else is kept as given.
+fn format_upload_date(date: &str) -> String {
+    if date.len() == 8 && date.bytes().all(|b| b.is_ascii_digit()) {
+        format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..])
+    } else {
+        date.to_string()
+    }
+}
+
+/// `1234567` as `1,234,567`.
+fn thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// How much the hash dedup collapsed the frame set.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq)]
+struct DedupStats {
+    frames_examined: usize,
+    slides_kept: usize,
+    frames_merged: usize,
+    merge_ratio: f64,
+}
+
+/// Wall-clock seconds spent in each stage.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+struct Timings {
+    dedup_secs: f64,
+    ocr_secs: f64,
+    total_secs: f64,
 }
 
 #[derive(Deserialize)]
@@ -140,27 +573,111 @@ struct ChatMsg {
 
 // ── Main pipeline ───────────────────────────────────────────────────────────
 
-#[tokio::main]
-async fn main() -> R<()> {
-    let args = Args::parse();
+fn main() -> R<()> {
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    args.settings = effective_settings(&matches);
+    args.validate()?;
+    // One ceiling for every pool: rayon runs the hashing/encoding passes,
+    // tokio's blocking pool the per-slide decode/resize next to the API calls
+    let jobs = args
+        .jobs
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(4, |n| n.get()));
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build_global()?;
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(jobs)
+        .max_blocking_threads(jobs)
+        .enable_all()
+        .build()?
+        .block_on(run(args))
+}
 
-    let slides_dir = Path::new(&args.output).join("slides");
-    std::fs::create_dir_all(&slides_dir)?;
+async fn run(args: Args) -> R<()> {
+    let started = std::time::Instant::now();
+    let progress = Progress::new(args.progress);
+    if args.verbose {
+        let argv: Vec<String> = std::env::args().map(|a| format!("{:?}", a)).collect();
+        eprintln!("  command: {}", argv.join(" "));
+        eprintln!("  settings: {}", settings_line(&args.settings));
+    }
+    let mut timings = Timings::default();
+
+    if let Some(ref dir) = args.render_only {
+        return render_only(&args, Path::new(dir));
+    }
+    if let Some(ref dir) = args.build_index {
+        return build_index(Path::new(dir));
+    }
+    let frames_dir = args.frames.as_deref().ok_or("--frames is required")?;
+
+    // `--output -`: slides are staged in a scratch dir and only the report is kept
+    let to_stdout = args.output == "-";
+    let scratch = if to_stdout {
+        Some(ScratchDir::create()?)
+    } else {
+        None
+    };
+    let output_dir = match &scratch {
+        Some(dir) => dir.0.clone(),
+        None => PathBuf::from(&args.output),
+    };
+    let output_dir = output_dir.as_path();
+    if to_stdout && normalize_path(Path::new(frames_dir)).starts_with(normalize_path(output_dir)) {
+        return Err(format!(
+            "--frames {} is inside the --output - scratch dir {}, which is deleted after the run",
+            frames_dir,
+            output_dir.display()
+        )
+        .into());
+    }
+    if output_dir.exists() && !output_dir.is_dir() {
+        return Err(format!(
+            "--output {} exists but is not a directory; pass a directory path",
+            output_dir.display()
+        )
+        .into());
+    }
+    // Lock before anything slow so a second run fails fast; --autotune writes nothing
+    let _lock = if args.autotune {
+        None
+    } else {
+        std::fs::create_dir_all(output_dir)?;
+        Some(lock_output_dir(output_dir)?)
+    };
 
     // 1. Load transcript if provided
     let (full_transcript, segments) = if let Some(ref path) = args.transcript {
         let data = std::fs::read_to_string(path)?;
         let tf: TranscriptFile = serde_json::from_str(&data)?;
         let segs = tf.segments.unwrap_or_default();
-        eprintln!("[1/4] Transcript loaded ({} segments)", segs.len());
+        progress.stage(
+            1,
+            "transcript",
+            &format!("Transcript loaded ({} segments)", segs.len()),
+        );
         (tf.text, segs)
+    } else if let Some(ref path) = args.subtitles {
+        let segs = parse_subtitles(&std::fs::read_to_string(path)?);
+        let text = segs
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        progress.stage(
+            1,
+            "transcript",
+            &format!("Subtitles loaded ({} cues)", segs.len()),
+        );
+        (text, segs)
     } else {
-        eprintln!("[1/4] No transcript provided, skipping");
+        progress.stage(1, "transcript", "No transcript provided, skipping");
         (String::new(), vec![])
     };
 
     // 2. Read + dedup frames
-    let mut frame_paths: Vec<PathBuf> = std::fs::read_dir(&args.frames)?
+    let mut frame_paths: Vec<PathBuf> = std::fs::read_dir(frames_dir)?
         .filter_map(|e| e.ok().map(|e| e.path()))
         .filter(|p| {
             p.extension()
@@ -169,12 +686,8 @@ async fn main() -> R<()> {
         .collect();
     frame_paths.sort();
 
-    if frame_paths.is_empty() {
-        return Err(format!("No image files found in {}", args.frames).into());
-    }
-
     // Load real timestamps if available (from ffmpeg scene detection)
-    let timestamps_file = Path::new(&args.frames).join("timestamps.txt");
+    let timestamps_file = Path::new(frames_dir).join("timestamps.txt");
     let real_timestamps: Vec<f64> = if timestamps_file.exists() {
         std::fs::read_to_string(&timestamps_file)?
             .lines()
@@ -183,6 +696,11 @@ async fn main() -> R<()> {
     } else {
         vec![]
     };
+    let frame_paths = single_format_frames(frame_paths, real_timestamps.len());
+
+    if frame_paths.is_empty() {
+        return Err(format!("No image files found in {}", frames_dir).into());
+    }
 
     // Detect slide region — try several frames to find one with a visible stage layout
     let client = reqwest::Client::builder()
@@ -206,34 +724,120 @@ async fn main() -> R<()> {
             }
         }
     }
-    match &slide_region {
-        Some(r) => eprintln!(
+    progress.detail(&match &slide_region {
+        Some(r) => format!(
             "  slide region: {}%,{}% {}%x{}%",
             r.x_pct as u32, r.y_pct as u32, r.w_pct as u32, r.h_pct as u32
         ),
-        None => eprintln!("  slide region: full frame (could not detect)"),
+        None => "  slide region: full frame (could not detect)".to_string(),
+    });
+
+    // Cheap check before the OCR calls: talking-head footage has no static
+    // stretches. Only the slide region counts, so a speaker moving next to a
+    // static slide doesn't look slideless.
+    if let Some(reason) = slideless_hint(&frame_paths, slide_region, &AverageHasher) {
+        if args.require_slides {
+            return Err(format!("video looks slideless: {} (--require-slides)", reason).into());
+        }
+        eprintln!("  warning: video may have no slides: {}", reason);
     }
 
-    let unique_frames = dedup_frames(&frame_paths, args.threshold, slide_region);
-    eprintln!(
-        "[2/4] Dedup: {} frames -> {} unique",
+    if args.autotune {
+        let hashes = hash_frames(&frame_paths, slide_region, &AverageHasher);
+        let colors = args
+            .color_aware_dedup
+            .then(|| color_histograms(&frame_paths, slide_region));
+        let (counts, recommended) =
+            autotune(&frame_paths, &hashes, colors.as_deref(), &AverageHasher);
+        progress.output("threshold  unique");
+        for (threshold, unique) in counts {
+            let mark = if threshold == recommended {
+                "  <- recommended"
+            } else {
+                ""
+            };
+            progress.output(&format!("{:>9.2}  {:>6}{}", threshold, unique, mark));
+        }
+        progress.output(&format!("recommended: --threshold {:.2}", recommended));
+        return Ok(());
+    }
+    let slides_dir = output_dir.join("slides");
+    std::fs::create_dir_all(&slides_dir)?;
+
+    let dedup_started = std::time::Instant::now();
+    let dedup = dedup_frames(
+        &frame_paths,
+        args.threshold,
+        slide_region,
+        &AverageHasher,
+        args.color_aware_dedup,
+    );
+    timings.dedup_secs = dedup_started.elapsed().as_secs_f64();
+    if let Some(ref path) = args.dump_similarity {
+        write_similarity_csv(Path::new(path), &dedup.comparisons)?;
+        progress.detail(&format!(
+            "  similarity: {} comparisons -> {}",
+            dedup.comparisons.len(),
+            path
+        ));
+    }
+    if let Some(ref path) = args.dump_frames {
+        let frames = frame_entries(&frame_paths, &dedup.hashes, &real_timestamps, args.interval);
+        write_atomic(Path::new(path), |out| {
+            Ok(serde_json::to_writer_pretty(out, &frames)?)
+        })?;
+        progress.detail(&format!("  frames: {} entries -> {}", frames.len(), path));
+    }
+    let dedup_stats = dedup.stats();
+    let unique_frames = if args.pick_sharpest {
+        sharpest_representatives(&dedup)
+    } else {
+        dedup.unique
+    };
+    let duration = real_timestamps
+        .last()
+        .copied()
+        .unwrap_or((frame_paths.len() as u64 * args.interval) as f64);
+    if let Some(advice) = threshold_advice(
         frame_paths.len(),
-        unique_frames.len()
+        unique_frames.len(),
+        duration,
+        args.threshold,
+    ) {
+        eprintln!("  warning: {}", advice);
+    }
+    progress.stage(
+        2,
+        "dedup",
+        &format!(
+            "Dedup: {} frames -> {} unique",
+            frame_paths.len(),
+            unique_frames.len()
+        ),
     );
 
     // 3. Vision OCR + classification
+    let ocr_started = std::time::Instant::now();
     let sem = std::sync::Arc::new(Semaphore::new(args.concurrency));
     let mut handles = Vec::new();
 
     for (i, frame_path) in unique_frames.iter().enumerate() {
         let client = client.clone();
-        let sem = sem.clone();
+        let progress = progress.clone();
+        let max_width = args.max_slide_width;
         let model = args.model.clone();
         let api = args.vision_api.clone();
         let path = frame_path.clone();
-        let dest = slides_dir.join(format!("slide_{:04}.jpg", i + 1));
+        // Keep the frame's own format so PNG frames stay lossless in the report
+        let ext = frame_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("jpg")
+            .to_lowercase();
+        let dest = slides_dir.join(format!("slide_{:04}.{}", i + 1, ext));
 
-        // Use real timestamp if available, otherwise estimate from index
+        // Use real timestamp if available, otherwise estimate from the
+        // frame's position in the extracted sequence
         let frame_idx = frame_paths
             .iter()
             .position(|p| p == frame_path)
@@ -241,22 +845,36 @@ async fn main() -> R<()> {
         let timestamp = real_timestamps
             .get(frame_idx)
             .copied()
-            .unwrap_or(i as f64 * args.interval as f64);
+            .unwrap_or(frame_idx as f64 * args.interval as f64);
 
-        handles.push(tokio::spawn(async move {
-            let _permit = sem.acquire().await.unwrap();
-            eprintln!("  ocr: {}", path.file_name().unwrap().to_str().unwrap());
+        handles.push(spawn_limited(&sem, async move {
+            let name = path.file_name().map_or_else(
+                || path.display().to_string(),
+                |n| n.to_string_lossy().into_owned(),
+            );
+            progress.detail(&format!("  ocr: {}", name));
             let result = vision_ocr(&client, &path, &model, &api).await;
             match result {
                 Ok(Some(text)) => {
-                    let _ = std::fs::copy(&path, &dest);
+                    let preserved = tokio::task::spawn_blocking({
+                        let (path, dest) = (path.clone(), dest.clone());
+                        move || preserve_slide(&path, &dest, max_width).map_err(|e| e.to_string())
+                    })
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|r| r);
+                    if let Err(e) = preserved {
+                        eprintln!("    -> skipping slide, image not usable: {}", e);
+                        let _ = std::fs::remove_file(&dest);
+                        return Err(format!("{}: {}", name, e));
+                    }
                     // label: SLIDE (with OCR text)
-                    Some((i, timestamp, dest, text, path, "SLIDE".to_string()))
+                    Ok((i, timestamp, dest, text, path, "SLIDE".to_string()))
                 }
                 Ok(None) => {
-                    eprintln!("    -> NOT_SLIDE");
+                    progress.detail("    -> NOT_SLIDE");
                     // label: NOT_SLIDE (still record for training)
-                    Some((
+                    Ok((
                         i,
                         timestamp,
                         PathBuf::new(),
@@ -267,7 +885,7 @@ async fn main() -> R<()> {
                 }
                 Err(e) => {
                     eprintln!("    -> error: {}", e);
-                    None
+                    Err(format!("{}: {}", name, e))
                 }
             }
         }));
@@ -275,118 +893,697 @@ async fn main() -> R<()> {
 
     let mut slides: Vec<SlideData> = Vec::new();
     let mut training_labels: Vec<(PathBuf, String)> = Vec::new();
-    for handle in handles {
-        if let Some((idx, ts, dest, text, src, label)) = handle.await? {
-            training_labels.push((src, label.clone()));
-            if label == "SLIDE" {
-                slides.push(SlideData {
-                    index: idx + 1,
-                    timestamp: ts,
-                    image_path: dest,
-                    text,
-                    transcript: String::new(),
-                });
+    let mut ocr_errors: Vec<String> = Vec::new();
+    let ocr_total = handles.len();
+    for (done, handle) in handles.into_iter().enumerate() {
+        let result = handle.await?;
+        progress.item("ocr", done + 1, ocr_total, ocr_started);
+        match result {
+            Ok((idx, ts, dest, text, src, label)) => {
+                training_labels.push((src, label.clone()));
+                if label == "SLIDE" {
+                    slides.push(SlideData {
+                        index: idx + 1,
+                        timestamp: ts,
+                        image_path: dest,
+                        text,
+                        transcript: String::new(),
+                        hash: None,
+                        change: None,
+                    });
+                }
             }
+            Err(e) => ocr_errors.push(e),
         }
     }
-    slides.sort_by(|a, b| a.index.cmp(&b.index));
+    slides.sort_by_key(|s| s.index);
+    timings.ocr_secs = ocr_started.elapsed().as_secs_f64();
 
     // Text-based dedup: remove slides with duplicate/near-duplicate OCR text
     let before_text_dedup = slides.len();
-    slides = dedup_by_text(slides);
-    eprintln!(
-        "[3/4] OCR done: {} slides ({} removed as text duplicates)",
-        slides.len(),
-        before_text_dedup - slides.len()
+    slides = dedup_by_text(slides, args.text_threshold);
+    progress.stage(
+        3,
+        "ocr",
+        &format!(
+            "OCR done: {} slides ({} removed as text duplicates)",
+            slides.len(),
+            before_text_dedup - slides.len()
+        ),
     );
 
+    if let Some(max) = args.max_slides {
+        let before = slides.len();
+        slides = cap_slides(slides, max);
+        if slides.len() < before {
+            progress.detail(&format!(
+                "  --max-slides {}: kept {} of {} slides",
+                max,
+                slides.len(),
+                before
+            ));
+        }
+    }
+
+    remove_stale_slides(&slides_dir, &slides);
+
     // Save training data in background
-    save_training_data(&training_labels);
+    save_training_data(&training_labels, &progress);
 
     // Assign transcript segments to slides
     assign_segments(&mut slides, &segments, args.interval as f64);
 
-    // 4. Generate markdown
-    let report_path = Path::new(&args.output).join("report.md");
-    generate_markdown(
-        &args.title,
-        args.url.as_deref().unwrap_or(""),
+    // 4. Generate the report document(s)
+    let video = VideoInfo::from_args(&args);
+    let meta = ReportMeta {
+        title: &args.title,
+        url: args.url.as_deref().unwrap_or(""),
+        video: &video,
+        duration,
+        front_matter: args.front_matter,
+        embed_images: args.embed_images,
+        labels: args.report_language.labels(),
+        dedup: dedup_stats,
+        run_settings: &args.settings,
+    };
+    verify_slide_images(&slides)?;
+    slides.par_iter_mut().for_each(|slide| {
+        slide.hash = hash_frame(&slide.image_path, None, &AverageHasher).ok();
+    });
+    if let Some(ref path) = args.diff_against {
+        let baseline: ExtractionReport = serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| format!("--diff-against {}: {}", path, e))?;
+        mark_changes(
+            &mut slides,
+            &baseline.slides,
+            args.threshold,
+            &AverageHasher,
+        );
+    }
+    if to_stdout {
+        let format = args.format.first().copied().unwrap_or(ReportFormat::Md);
+        let result = render_report(
+            format,
+            &meta,
+            &full_transcript,
+            &slides,
+            &mut std::io::stdout(),
+        );
+        result?;
+        progress.stage(4, "report", "Report written to stdout");
+        if slides.is_empty() && args.fail_on_empty {
+            return Err("no slides detected (--fail-on-empty)".into());
+        }
+        return Ok(());
+    }
+    let previous_docs = previous_documents(output_dir);
+    let (written, failed) = write_reports(
+        &args.format,
+        &args.report_name,
+        &meta,
         &full_transcript,
         &slides,
-        &report_path,
-    )?;
-    eprintln!("[4/4] Report: {}", report_path.display());
+        output_dir,
+    );
+    if written.is_empty() {
+        return Err(failed.join("; ").into());
+    }
+    // A failed format keeps its previous document, so only a clean write prunes
+    if failed.is_empty() {
+        remove_stale_documents(output_dir, &previous_docs, &written);
+    }
+    let names: Vec<String> = written.iter().map(|p| p.display().to_string()).collect();
+    progress.stage(4, "report", &format!("Report: {}", names.join(", ")));
+    let report_path = written[0].clone();
+    let output_bytes = dir_size(&slides_dir)
+        + written
+            .iter()
+            .map(|p| std::fs::metadata(p).map_or(0, |m| m.len()))
+            .sum::<u64>();
+
+    timings.total_secs = started.elapsed().as_secs_f64();
+    let report = ExtractionReport {
+        title: args.title.clone(),
+        url: args.url.clone().unwrap_or_default(),
+        duration_secs: duration,
+        transcript: full_transcript,
+        video,
+        frames_total: frame_paths.len(),
+        frames_unique: unique_frames.len(),
+        dedup: dedup_stats,
+        slides,
+        report_path,
+        timings,
+        ocr_errors,
+        output_bytes,
+        settings: args.settings.clone(),
+        documents: document_names(&written),
+    };
+    let json_path = output_dir.join("report.json");
+    write_atomic(&json_path, |out| {
+        Ok(serde_json::to_writer_pretty(out, &report)?)
+    })?;
+    progress.detail(&format!("  output size: {}", human_bytes(output_bytes)));
+
+    if report.slides.is_empty() {
+        eprintln!(
+            "warning: no slides detected; see {} for suggestions",
+            report.report_path.display()
+        );
+        if args.fail_on_empty {
+            return Err("no slides detected (--fail-on-empty)".into());
+        }
+    }
+    if !failed.is_empty() {
+        return Err(failed.join("; ").into());
+    }
 
     Ok(())
 }
 
-// ── Slide region detection ──────────────────────────────────────────────────
+/// Regenerate the markdown from a saved report.json, skipping frames and OCR.
+fn render_only(args: &Args, dir: &Path) -> R<()> {
+    let json_path = dir.join("report.json");
+    if !json_path.exists() {
+        return Err(format!(
+            "{} not found; --render-only needs the output directory of a completed run",
+            json_path.display()
+        )
+        .into());
+    }
+    let _lock = lock_output_dir(dir)?;
+    let mut report: ExtractionReport = serde_json::from_str(&std::fs::read_to_string(&json_path)?)?;
+    // Stored paths are relative to the original run's cwd; the report links slides/<name>
+    for slide in &mut report.slides {
+        if let Some(name) = slide.image_path.file_name() {
+            slide.image_path = dir.join("slides").join(name);
+        }
+    }
+    let meta = ReportMeta {
+        title: &report.title,
+        url: &report.url,
+        video: &report.video,
+        duration: report.duration_secs,
+        front_matter: args.front_matter,
+        embed_images: args.embed_images,
+        labels: args.report_language.labels(),
+        dedup: report.dedup,
+        run_settings: &report.settings,
+    };
+    verify_slide_images(&report.slides)?;
+    let previous_docs = previous_documents(dir);
+    let (written, failed) = write_reports(
+        &args.format,
+        &args.report_name,
+        &meta,
+        &report.transcript,
+        &report.slides,
+        dir,
+    );
+    // Slides are untouched, so earlier documents stay valid: keep them and
+    // record the new ones next to them, without rewriting the rest of report.json
+    let mut documents = previous_docs;
+    for name in document_names(&written) {
+        if !documents.contains(&name) {
+            documents.push(name);
+        }
+    }
+    let mut stored: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&json_path)?)?;
+    stored["documents"] = serde_json::json!(documents);
+    write_atomic(&json_path, |out| {
+        Ok(serde_json::to_writer_pretty(out, &stored)?)
+    })?;
+    for path in &written {
+        eprintln!(
+            "Report re-rendered: {} ({} slides)",
+            path.display(),
+            report.slides.len()
+        );
+    }
+    if !failed.is_empty() {
+        return Err(failed.join("; ").into());
+    }
+    Ok(())
+}
 
-const REGION_PROMPT: &str = "\
-This frame is from a video recording of a presentation. A slide is projected on a screen somewhere in the frame.
+/// Report documents an earlier run recorded in `dir`'s report.json (reports
+/// written before `documents` existed only name their first, `report_path`).
+fn previous_documents(dir: &Path) -> Vec<String> {
+    let Ok(data) = std::fs::read_to_string(dir.join("report.json")) else {
+        return Vec::new();
+    };
+    let Ok(stored) = serde_json::from_str::<serde_json::Value>(&data) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = stored["documents"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str().map(str::to_string))
+        .collect();
+    if let Some(name) = stored["report_path"]
+        .as_str()
+        .and_then(|p| Path::new(p).file_name())
+        .and_then(|n| n.to_str())
+    {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
 
-Return the bounding box of ONLY the projected slide/screen area as four integers: x y w h
-where x,y is the top-left corner and w,h is width and height, all as percentages (0-100) of the frame dimensions.
+/// Delete documents an earlier run left in `dir` that this one didn't rewrite
+/// (another --format or --report-name); they'd link slides that are gone.
+fn remove_stale_documents(dir: &Path, previous: &[String], written: &[PathBuf]) {
+    let current = document_names(written);
+    for name in previous {
+        // Only plain file names next to report.json, never report.json itself
+        let plain = Path::new(name)
+            .file_name()
+            .is_some_and(|n| n == name.as_str());
+        if !plain || name == "report.json" || current.contains(name) {
+            continue;
+        }
+        if std::fs::remove_file(dir.join(name)).is_ok() {
+            eprintln!("  removed stale report: {}", dir.join(name).display());
+        }
+    }
+}
 
-Respond with ONLY four numbers separated by spaces, nothing else. Example: 30 5 45 55";
+fn document_names(paths: &[PathBuf]) -> Vec<String> {
+    paths
+        .iter()
+        .filter_map(|p| p.file_name())
+        .map(|n| n.to_string_lossy().into_owned())
+        .collect()
+}
 
-#[derive(Clone, Copy)]
-struct CropRegion {
-    x_pct: f64,
-    y_pct: f64,
-    w_pct: f64,
-    h_pct: f64,
+/// Total size of the regular files under `dir`, recursively; unreadable
+/// entries count as zero.
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| match e.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&e.path()),
+            Ok(t) if t.is_file() => e.metadata().map_or(0, |m| m.len()),
+            _ => 0,
+        })
+        .sum()
 }
 
-async fn detect_slide_region(
-    client: &reqwest::Client,
-    path: &Path,
-    model: &str,
-    api_base: &str,
-) -> R<Option<CropRegion>> {
-    let image_data = tokio::task::spawn_blocking({
-        let path = path.to_path_buf();
-        move || resize_image(&path)
-    })
-    .await??;
-    let b64 = general_purpose::STANDARD.encode(&image_data);
-    let data_url = format!("data:image/jpeg;base64,{}", b64);
+/// Time left for the `total - processed` remaining items at the per-item rate
+/// measured so far (wall-clock, so it accounts for request concurrency).
+fn projected_remaining(elapsed_secs: f64, processed: usize, total: usize) -> f64 {
+    if processed == 0 {
+        return 0.0;
+    }
+    elapsed_secs / processed as f64 * total.saturating_sub(processed) as f64
+}
 
-    let request = ChatRequest {
-        model: model.to_string(),
-        messages: vec![ChatMessage {
-            role: "user".to_string(),
-            content: vec![
-                ContentPart::Text {
-                    text: REGION_PROMPT.to_string(),
-                },
-                ContentPart::ImageUrl {
-                    image_url: ImageUrlData { url: data_url },
-                },
-            ],
-        }],
-    };
+/// `42s` / `3m05s` / `1h02m`-style duration.
+fn human_duration(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
 
-    let resp: ChatResponse = client
-        .post(format!("{}/chat/completions", api_base))
-        .json(&request)
-        .send()
-        .await?
-        .error_for_status()?
-        .json()
-        .await?;
+/// `14.2 MiB`-style size, in powers of 1024 like `du -h`.
+fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64;
+    let mut unit = "B";
+    for u in UNITS {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = u;
+    }
+    format!("{:.1} {}", size, unit)
+}
 
-    let content = resp
-        .choices
-        .first()
-        .map(|c| c.message.content.trim().to_string())
-        .unwrap_or_default();
+/// Write `path` via a sibling temp file and a rename, so readers (and a later
+/// --render-only) see either the old file or the complete new one, never a
+/// truncated write from a crash or full disk.
+fn write_atomic(path: &Path, write: impl FnOnce(&mut dyn Write) -> R<()>) -> R<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+    let result = (|| -> R<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(&tmp)?);
+        write(&mut file)?;
+        file.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result
+}
 
-    // Parse "x y w h" from response
-    let nums: Vec<f64> = content
-        .split_whitespace()
-        .filter_map(|s| {
-            s.trim_matches(|c: char| !c.is_ascii_digit() && c != '.')
+/// Fresh private temp directory for `--output -`, removed when dropped so an
+/// early error return doesn't leave staged slides behind. `create_dir` refuses
+/// an existing path, so nothing pre-planted in a shared /tmp is ever used.
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    fn create() -> R<Self> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.subsec_nanos());
+        let mut last_err = None;
+        for attempt in 0..8u32 {
+            let path = std::env::temp_dir().join(format!(
+                "yt-sl-{}-{:08x}",
+                std::process::id(),
+                nanos.wrapping_add(attempt.wrapping_mul(0x9e37_79b9))
+            ));
+            let mut builder = std::fs::DirBuilder::new();
+            #[cfg(unix)]
+            std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+            match builder.create(&path) {
+                Ok(()) => return Ok(Self(path)),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(format!(
+            "could not create a scratch directory: {}",
+            last_err.unwrap()
+        )
+        .into())
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Advisory lock on `<output>/.lock`, held until the returned file is dropped,
+/// so two runs can't interleave writes to the same slides/ and reports. The OS
+/// releases it if the process dies, so a leftover .lock file is harmless.
+fn lock_output_dir(dir: &Path) -> R<std::fs::File> {
+    let path = dir.join(".lock");
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)?;
+    match file.try_lock() {
+        Ok(()) => Ok(file),
+        Err(std::fs::TryLockError::WouldBlock) => Err(format!(
+            "another yt-sl run is already writing to {}; wait for it or pick a different --output",
+            dir.display()
+        )
+        .into()),
+        Err(std::fs::TryLockError::Error(e)) => {
+            Err(format!("could not lock {}: {}", path.display(), e).into())
+        }
+    }
+}
+
+/// A frames directory reused across `--frame-format` changes can hold frames
+/// from two extractions. Their numbering and timestamps.txt only line up within
+/// one of them, so keep the format whose frame count matches the `timestamps`
+/// lines (the latest extraction's), or else the one with the most frames.
+fn single_format_frames(paths: Vec<PathBuf>, timestamps: usize) -> Vec<PathBuf> {
+    let format = |p: &Path| match p.extension().and_then(|e| e.to_str()) {
+        Some("png") => "png",
+        _ => "jpg",
+    };
+    let pngs = paths.iter().filter(|p| format(p) == "png").count();
+    let jpgs = paths.len() - pngs;
+    if pngs == 0 || jpgs == 0 {
+        return paths;
+    }
+    let keep = match (pngs == timestamps, jpgs == timestamps) {
+        (true, false) if timestamps > 0 => "png",
+        (false, true) if timestamps > 0 => "jpg",
+        // Ties go to png, the lossless (and usually more recent) choice
+        _ if pngs >= jpgs => "png",
+        _ => "jpg",
+    };
+    eprintln!(
+        "  warning: frames directory mixes {} jpg and {} png frames; using only the {} frames",
+        jpgs, pngs, keep
+    );
+    paths.into_iter().filter(|p| format(p) == keep).collect()
+}
+
+// ── Search index ────────────────────────────────────────────────────────────
+
+#[derive(Serialize, PartialEq)]
+struct IndexEntry {
+    video: String,
+    report: PathBuf,
+    slide: usize,
+}
+
+/// Collect DIR/report.json and DIR/*/report.json (the wrapper's layout) and
+/// write an inverted index of OCR tokens to DIR/index.json.
+fn build_index(dir: &Path) -> R<()> {
+    let mut reports = vec![dir.join("report.json")];
+    for entry in std::fs::read_dir(dir)?.filter_map(|e| e.ok()) {
+        reports.push(entry.path().join("report.json"));
+    }
+    reports.retain(|p| p.is_file());
+    reports.sort();
+    if reports.is_empty() {
+        return Err(format!(
+            "no report.json found in {} or its subdirectories",
+            dir.display()
+        )
+        .into());
+    }
+
+    let mut index: std::collections::BTreeMap<String, Vec<IndexEntry>> = Default::default();
+    for path in &reports {
+        let report: ExtractionReport = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        for slide in &report.slides {
+            for token in tokenize(&slide.text) {
+                let entry = IndexEntry {
+                    video: report.title.clone(),
+                    report: path.clone(),
+                    slide: slide.index,
+                };
+                let refs = index.entry(token).or_default();
+                if !refs.contains(&entry) {
+                    refs.push(entry);
+                }
+            }
+        }
+    }
+
+    let out = dir.join("index.json");
+    write_atomic(&out, |w| Ok(serde_json::to_writer_pretty(w, &index)?))?;
+    eprintln!(
+        "Index: {} tokens from {} report(s) -> {}",
+        index.len(),
+        reports.len(),
+        out.display()
+    );
+    Ok(())
+}
+
+/// Lowercase and split on anything that isn't alphanumeric.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+// ── Progress reporting ──────────────────────────────────────────────────────
+
+const STAGE_COUNT: usize = 4;
+
+/// Items a stage must finish before text mode prints a time estimate, so it's
+/// based on measured throughput rather than the first (often slowest) request.
+const ESTIMATE_AFTER: usize = 3;
+
+#[derive(Clone, Copy, PartialEq, Debug, ValueEnum)]
+enum ProgressMode {
+    Text,
+    Ndjson,
+    None,
+}
+
+/// Stage/item progress. Text mode keeps the human-readable stderr lines;
+/// NDJSON mode writes one `{stage, processed, total, percentage, eta_secs}`
+/// object per update to stdout for frontends tailing the output.
+#[derive(Clone)]
+struct Progress {
+    mode: ProgressMode,
+    /// Where NDJSON records go
+    out: std::sync::Arc<std::sync::Mutex<Box<dyn Write + Send>>>,
+}
+
+impl Progress {
+    fn new(mode: ProgressMode) -> Self {
+        Self::with_writer(mode, Box::new(std::io::stdout()))
+    }
+
+    fn with_writer(mode: ProgressMode, out: Box<dyn Write + Send>) -> Self {
+        Self {
+            mode,
+            out: std::sync::Arc::new(std::sync::Mutex::new(out)),
+        }
+    }
+
+    /// A pipeline stage finished.
+    fn stage(&self, step: usize, stage: &str, msg: &str) {
+        match self.mode {
+            ProgressMode::Text => eprintln!("[{}/{}] {}", step, STAGE_COUNT, msg),
+            ProgressMode::Ndjson => self.emit(stage, step, STAGE_COUNT, None),
+            ProgressMode::None => {}
+        }
+    }
+
+    /// One item of a long-running stage finished. NDJSON reports every item
+    /// with an ETA; text mode prints the projected time left once
+    /// [`ESTIMATE_AFTER`] items are done and then every 10 items.
+    fn item(&self, stage: &str, processed: usize, total: usize, since: std::time::Instant) {
+        if processed == 0 {
+            return;
+        }
+        let elapsed = since.elapsed().as_secs_f64();
+        let eta = projected_remaining(elapsed, processed, total);
+        match self.mode {
+            ProgressMode::Ndjson => self.emit(stage, processed, total, Some(eta)),
+            ProgressMode::Text
+                if processed < total
+                    && (processed == ESTIMATE_AFTER
+                        || (processed > ESTIMATE_AFTER && processed.is_multiple_of(10))) =>
+            {
+                eprintln!(
+                    "  {}: {}/{} done, ~{} left ({:.1}s each)",
+                    stage,
+                    processed,
+                    total,
+                    human_duration(eta),
+                    elapsed / processed as f64
+                )
+            }
+            _ => {}
+        }
+    }
+
+    /// Free-form informational line, shown in text mode only.
+    fn detail(&self, msg: &str) {
+        if self.mode == ProgressMode::Text {
+            eprintln!("{}", msg);
+        }
+    }
+
+    /// A line of a command's actual output (e.g. the --autotune table): stdout,
+    /// unless NDJSON records already own it.
+    fn output(&self, line: &str) {
+        if self.mode == ProgressMode::Ndjson {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    fn emit(&self, stage: &str, processed: usize, total: usize, eta_secs: Option<f64>) {
+        let percentage = if total == 0 {
+            0.0
+        } else {
+            (processed as f64 / total as f64).min(1.0)
+        };
+        let record = serde_json::json!({
+            "stage": stage,
+            "processed": processed,
+            "total": total,
+            "percentage": percentage,
+            "eta_secs": eta_secs,
+        });
+        let mut out = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = writeln!(out, "{}", record);
+        let _ = out.flush();
+    }
+}
+
+// ── Slide region detection ──────────────────────────────────────────────────
+
+const REGION_PROMPT: &str = "\
+This frame is from a video recording of a presentation. A slide is projected on a screen somewhere in the frame.
+
+Return the bounding box of ONLY the projected slide/screen area as four integers: x y w h
+where x,y is the top-left corner and w,h is width and height, all as percentages (0-100) of the frame dimensions.
+
+Respond with ONLY four numbers separated by spaces, nothing else. Example: 30 5 45 55";
+
+#[derive(Clone, Copy)]
+struct CropRegion {
+    x_pct: f64,
+    y_pct: f64,
+    w_pct: f64,
+    h_pct: f64,
+}
+
+async fn detect_slide_region(
+    client: &reqwest::Client,
+    path: &Path,
+    model: &str,
+    api_base: &str,
+) -> R<Option<CropRegion>> {
+    let image_data = tokio::task::spawn_blocking({
+        let path = path.to_path_buf();
+        move || resize_image(&path)
+    })
+    .await??;
+    let b64 = general_purpose::STANDARD.encode(&image_data);
+    let data_url = format!("data:image/jpeg;base64,{}", b64);
+
+    let request = ChatRequest {
+        model: model.to_string(),
+        messages: vec![ChatMessage {
+            role: "user".to_string(),
+            content: vec![
+                ContentPart::Text {
+                    text: REGION_PROMPT.to_string(),
+                },
+                ContentPart::ImageUrl {
+                    image_url: ImageUrlData { url: data_url },
+                },
+            ],
+        }],
+    };
+
+    let resp: ChatResponse = client
+        .post(format!("{}/chat/completions", api_base))
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let content = resp
+        .choices
+        .first()
+        .map(|c| c.message.content.trim().to_string())
+        .unwrap_or_default();
+
+    // Parse "x y w h" from response
+    let nums: Vec<f64> = content
+        .split_whitespace()
+        .filter_map(|s| {
+            s.trim_matches(|c: char| !c.is_ascii_digit() && c != '.')
                 .parse()
                 .ok()
         })
@@ -416,69 +1613,484 @@ fn crop_to_region(path: &Path, region: CropRegion) -> R<image::DynamicImage> {
     Ok(img.crop_imm(x, y, cw, ch))
 }
 
-fn avg_hash_cropped(path: &Path, region: Option<CropRegion>) -> R<u64> {
-    let gray = match region {
-        Some(r) => crop_to_region(path, r)?.to_luma8(),
-        None => image::open(path)?.to_luma8(),
-    };
-    let small = imageops::resize(&gray, HASH_SIZE, HASH_SIZE, imageops::FilterType::Lanczos3);
-    let mean = small.pixels().map(|p| p[0] as u64).sum::<u64>() / (HASH_SIZE * HASH_SIZE) as u64;
-    let mut hash: u64 = 0;
-    for (i, pixel) in small.pixels().enumerate() {
-        if pixel[0] as u64 >= mean {
-            hash |= 1 << i;
+// ── Perceptual hash dedup ───────────────────────────────────────────────────
+
+/// A frame fingerprint plus the similarity measure that goes with it. The
+/// fingerprint is opaque text (hex bits, an encoded embedding, ...) stored as-is
+/// in report.json; only the hasher that produced it ever compares it.
+trait FrameHasher: Sync {
+    fn hash(&self, img: &image::DynamicImage) -> String;
+    /// 1.0 for identical frames, 0.0 for nothing in common.
+    fn similarity(&self, a: &str, b: &str) -> f64;
+}
+
+/// The default: an 8x8 average hash of the letterbox-trimmed grayscale frame.
+struct AverageHasher;
+
+impl FrameHasher for AverageHasher {
+    /// 16 hex digits
+    fn hash(&self, img: &image::DynamicImage) -> String {
+        let gray = trim_letterbox(img.to_luma8());
+        let small = imageops::resize(&gray, HASH_SIZE, HASH_SIZE, imageops::FilterType::Lanczos3);
+        let mean =
+            small.pixels().map(|p| p[0] as u64).sum::<u64>() / (HASH_SIZE * HASH_SIZE) as u64;
+        let mut hash: u64 = 0;
+        for (i, pixel) in small.pixels().enumerate() {
+            if pixel[0] as u64 >= mean {
+                hash |= 1 << i;
+            }
+        }
+        format!("{:016x}", hash)
+    }
+
+    fn similarity(&self, a: &str, b: &str) -> f64 {
+        match (u64::from_str_radix(a, 16), u64::from_str_radix(b, 16)) {
+            (Ok(a), Ok(b)) => hamming_similarity(a, b),
+            _ => 0.0,
         }
     }
-    Ok(hash)
 }
 
-// ── Perceptual hash dedup ───────────────────────────────────────────────────
+fn hash_frame(path: &Path, region: Option<CropRegion>, hasher: &dyn FrameHasher) -> R<String> {
+    let img = match region {
+        Some(r) => crop_to_region(path, r)?,
+        None => image::open(path)?,
+    };
+    Ok(hasher.hash(&img))
+}
 
-fn avg_hash(path: &Path) -> R<u64> {
-    let img = image::open(path)?.to_luma8();
-    let small = imageops::resize(&img, HASH_SIZE, HASH_SIZE, imageops::FilterType::Lanczos3);
-    let mean = small.pixels().map(|p| p[0] as u64).sum::<u64>() / (HASH_SIZE * HASH_SIZE) as u64;
-    let mut hash: u64 = 0;
-    for (i, pixel) in small.pixels().enumerate() {
-        if pixel[0] as u64 >= mean {
-            hash |= 1 << i;
-        }
+/// Crop symmetric black bars (letterbox/pillarbox) so the same slide hashes the
+/// same whether or not it was padded to a different aspect ratio. Asymmetric or
+/// very large dark margins are left alone since those are usually slide content.
+fn trim_letterbox(img: image::GrayImage) -> image::GrayImage {
+    const DARK: u8 = 24;
+    let (w, h) = img.dimensions();
+    let row_dark = |y: u32| (0..w).all(|x| img.get_pixel(x, y)[0] < DARK);
+    let col_dark = |x: u32| (0..h).all(|y| img.get_pixel(x, y)[0] < DARK);
+
+    let top = (0..h).take_while(|&y| row_dark(y)).count() as u32;
+    if top == h {
+        return img;
+    }
+    let bottom = (0..h).rev().take_while(|&y| row_dark(y)).count() as u32;
+    let left = (0..w).take_while(|&x| col_dark(x)).count() as u32;
+    let right = (0..w).rev().take_while(|&x| col_dark(x)).count() as u32;
+
+    let bars = |a: u32, b: u32, len: u32| a.abs_diff(b) <= len / 50 + 2 && a.max(b) <= len / 4;
+    let (y0, y1) = if bars(top, bottom, h) {
+        (top, h - bottom)
+    } else {
+        (0, h)
+    };
+    let (x0, x1) = if bars(left, right, w) {
+        (left, w - right)
+    } else {
+        (0, w)
+    };
+    if (x0, y0, x1, y1) == (0, 0, w, h) {
+        return img;
     }
-    Ok(hash)
+    imageops::crop_imm(&img, x0, y0, x1 - x0, y1 - y0).to_image()
 }
 
 fn hamming_similarity(a: u64, b: u64) -> f64 {
     1.0 - (a ^ b).count_ones() as f64 / 64.0
 }
 
-fn dedup_frames(paths: &[PathBuf], threshold: f64, region: Option<CropRegion>) -> Vec<PathBuf> {
-    if paths.is_empty() {
-        return vec![];
-    }
+/// The closest already-accepted frame for one candidate, i.e. the pair that
+/// decided whether it was merged.
+struct Comparison {
+    frame: PathBuf,
+    closest: PathBuf,
+    similarity: f64,
+    merged: bool,
+}
 
-    let hash_fn = |p: &Path| -> R<u64> {
-        if region.is_some() {
-            avg_hash_cropped(p, region)
-        } else {
-            avg_hash(p)
+struct DedupResult {
+    unique: Vec<PathBuf>,
+    comparisons: Vec<Comparison>,
+    /// Every input frame's hash, in input order (`None` where hashing failed).
+    hashes: Vec<Option<String>>,
+}
+
+impl DedupResult {
+    /// Every successfully hashed frame is either kept or merged into a kept one.
+    fn stats(&self) -> DedupStats {
+        let merged = self.comparisons.iter().filter(|c| c.merged).count();
+        let examined = self.unique.len() + merged;
+        DedupStats {
+            frames_examined: examined,
+            slides_kept: self.unique.len(),
+            frames_merged: merged,
+            merge_ratio: if examined == 0 {
+                0.0
+            } else {
+                merged as f64 / examined as f64
+            },
         }
-    };
+    }
+}
+
+fn dedup_frames(
+    paths: &[PathBuf],
+    threshold: f64,
+    region: Option<CropRegion>,
+    hasher: &dyn FrameHasher,
+    color_aware: bool,
+) -> DedupResult {
+    let hashes = hash_frames(paths, region, hasher);
+    let colors = color_aware.then(|| color_histograms(paths, region));
+    dedup_hashes(paths, &hashes, colors.as_deref(), threshold, hasher)
+}
+
+/// Decoding + hashing dominates; do it in parallel. A frame that can't be
+/// hashed is warned about and left as `None`.
+fn hash_frames(
+    paths: &[PathBuf],
+    region: Option<CropRegion>,
+    hasher: &dyn FrameHasher,
+) -> Vec<Option<String>> {
+    let hashes: Vec<R<String>> = paths
+        .par_iter()
+        .map(|p| hash_frame(p, region, hasher))
+        .collect();
+    paths
+        .iter()
+        .zip(hashes)
+        .map(|(path, hash)| {
+            hash.map_err(|e| eprintln!("  warning: could not hash {}: {}", path.display(), e))
+                .ok()
+        })
+        .collect()
+}
 
-    let mut unique = vec![paths[0].clone()];
-    let mut accepted_hashes = vec![hash_fn(&paths[0]).unwrap_or(0)];
+/// Greedy in-order dedup: a frame is kept unless it is at least `threshold`
+/// similar to a frame kept before it. With `colors` (--color-aware-dedup) that
+/// frame must also have a similar color histogram.
+fn dedup_hashes(
+    paths: &[PathBuf],
+    hashes: &[Option<String>],
+    colors: Option<&[Option<ColorHistogram>]>,
+    threshold: f64,
+    hasher: &dyn FrameHasher,
+) -> DedupResult {
+    let mut result = DedupResult {
+        unique: Vec::new(),
+        comparisons: Vec::new(),
+        hashes: hashes.to_vec(),
+    };
+    // (hash, index into `paths`) of every kept frame
+    let mut accepted: Vec<(&str, usize)> = Vec::new();
 
-    for path in &paths[1..] {
-        if let Ok(hash) = hash_fn(path) {
-            let is_duplicate = accepted_hashes
+    for (i, (path, hash)) in paths.iter().zip(hashes).enumerate() {
+        // A failed hash carries no information; never let it stand in as a match
+        let Some(hash) = hash else {
+            continue;
+        };
+        let same_colors = |j: usize| {
+            colors.is_none_or(|c| colors_match(c[i].as_ref(), c[accepted[j].1].as_ref()))
+        };
+        let ranked = || {
+            accepted
                 .iter()
-                .any(|&h| hamming_similarity(h, hash) >= threshold);
-            if !is_duplicate {
-                unique.push(path.clone());
-                accepted_hashes.push(hash);
-            }
+                .enumerate()
+                .map(|(j, (h, _))| (j, hasher.similarity(h, hash)))
+        };
+        // Prefer the closest frame that may be merged into; otherwise record the
+        // closest overall
+        let closest = ranked()
+            .filter(|&(j, sim)| sim >= threshold && same_colors(j))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .or_else(|| ranked().max_by(|a, b| a.1.total_cmp(&b.1)));
+        let is_duplicate = closest.is_some_and(|(j, sim)| sim >= threshold && same_colors(j));
+        if let Some((j, similarity)) = closest {
+            result.comparisons.push(Comparison {
+                frame: path.clone(),
+                closest: result.unique[j].clone(),
+                similarity,
+                merged: is_duplicate,
+            });
+        }
+        if !is_duplicate {
+            result.unique.push(path.clone());
+            accepted.push((hash.as_str(), i));
         }
     }
-    unique
+    result
+}
+
+/// Histogram bins per RGB channel for --color-aware-dedup.
+const COLOR_BINS: usize = 8;
+
+/// Minimum histogram intersection for two frames to count as the same colors.
+/// Re-encoding noise and a moving cursor stay well above it; a recolored theme
+/// (dark vs. light, different syntax highlighting) falls well below.
+const COLOR_SIMILARITY: f64 = 0.8;
+
+/// Normalized per-channel RGB histograms, `COLOR_BINS` bins each.
+type ColorHistogram = [f32; COLOR_BINS * 3];
+
+/// Histograms for every frame, in parallel. A frame that can't be decoded is
+/// left as `None` and compared on its hash alone.
+fn color_histograms(paths: &[PathBuf], region: Option<CropRegion>) -> Vec<Option<ColorHistogram>> {
+    paths
+        .par_iter()
+        .map(|p| {
+            let img = match region {
+                Some(r) => crop_to_region(p, r),
+                None => image::open(p).map_err(Into::into),
+            };
+            img.map(|img| color_histogram(&img)).ok()
+        })
+        .collect()
+}
+
+fn color_histogram(img: &image::DynamicImage) -> ColorHistogram {
+    // Proportions survive downscaling and 64x64 is plenty to count them
+    let small = img.thumbnail(64, 64).to_rgb8();
+    let mut hist = [0f32; COLOR_BINS * 3];
+    for pixel in small.pixels() {
+        for (channel, &value) in pixel.0.iter().enumerate() {
+            hist[channel * COLOR_BINS + value as usize * COLOR_BINS / 256] += 1.0;
+        }
+    }
+    let total = (small.width() * small.height()).max(1) as f32;
+    hist.iter_mut().for_each(|count| *count /= total);
+    hist
+}
+
+/// Histogram intersection averaged over the three channels: 1.0 for identical
+/// color distributions, 0.0 for disjoint ones.
+fn histogram_similarity(a: &ColorHistogram, b: &ColorHistogram) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x.min(*y) as f64).sum::<f64>() / 3.0
+}
+
+fn colors_match(a: Option<&ColorHistogram>, b: Option<&ColorHistogram>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => histogram_similarity(a, b) >= COLOR_SIMILARITY,
+        _ => true,
+    }
+}
+
+/// Thresholds swept by `--autotune`.
+const AUTOTUNE_THRESHOLDS: [f64; 10] = [0.80, 0.82, 0.84, 0.86, 0.88, 0.90, 0.92, 0.94, 0.96, 0.98];
+
+/// Unique-frame count for each swept threshold, plus the recommended one: the
+/// middle of the longest stretch where the count stays within 10% (and at least
+/// one frame) of where the stretch began. Below it distinct slides merge, above
+/// it noise starts splitting one slide into several.
+fn autotune(
+    paths: &[PathBuf],
+    hashes: &[Option<String>],
+    colors: Option<&[Option<ColorHistogram>]>,
+    hasher: &dyn FrameHasher,
+) -> (Vec<(f64, usize)>, f64) {
+    let counts: Vec<(f64, usize)> = AUTOTUNE_THRESHOLDS
+        .iter()
+        .map(|&t| {
+            (
+                t,
+                dedup_hashes(paths, hashes, colors, t, hasher).unique.len(),
+            )
+        })
+        .collect();
+    let (mut best_start, mut best_len) = (0, 0);
+    for start in 0..counts.len() {
+        let base = counts[start].1;
+        let limit = base + (base / 10).max(1);
+        let len = counts[start..]
+            .iter()
+            .take_while(|(_, n)| *n <= limit)
+            .count();
+        if len > best_len {
+            (best_start, best_len) = (start, len);
+        }
+    }
+    let recommended = counts[best_start + (best_len - 1) / 2].0;
+    (counts, recommended)
+}
+
+/// Adjacent-frame pairs sampled by the slideless check.
+const SLIDELESS_SAMPLES: usize = 12;
+
+/// Compare a few evenly spaced pairs of neighbouring frames. While a slide is
+/// on screen its neighbours are near-identical, so a video where almost none
+/// are is likely continuous motion (talking head, demo footage). Returns a
+/// description of what was measured when it looks slideless.
+fn slideless_hint(
+    paths: &[PathBuf],
+    region: Option<CropRegion>,
+    hasher: &dyn FrameHasher,
+) -> Option<String> {
+    const STATIC_SIMILARITY: f64 = 0.95;
+    if paths.len() < SLIDELESS_SAMPLES * 2 {
+        return None;
+    }
+    let step = (paths.len() - 1) / SLIDELESS_SAMPLES;
+    let pairs: Vec<f64> = (0..SLIDELESS_SAMPLES)
+        .into_par_iter()
+        .filter_map(|i| {
+            let a = hash_frame(&paths[i * step], region, hasher).ok()?;
+            let b = hash_frame(&paths[i * step + 1], region, hasher).ok()?;
+            Some(hasher.similarity(&a, &b))
+        })
+        .collect();
+    let static_pairs = pairs.iter().filter(|&&s| s >= STATIC_SIMILARITY).count();
+    // At most one static pair in the sample: no slide stayed up long enough
+    (pairs.len() >= SLIDELESS_SAMPLES / 2 && static_pairs <= 1).then(|| {
+        format!(
+            "{} of {} sampled neighbouring frames are near-identical",
+            static_pairs,
+            pairs.len()
+        )
+    })
+}
+
+/// Frames sampled per duplicate group by `--pick-sharpest`, spread evenly over it.
+const SHARPNESS_CANDIDATES: usize = 5;
+
+/// For each kept frame, the sharpest of up to [`SHARPNESS_CANDIDATES`] frames
+/// merged into it (itself included). Catches the first frame of a slide being
+/// caught mid-transition or mid-refocus.
+fn sharpest_representatives(dedup: &DedupResult) -> Vec<PathBuf> {
+    dedup
+        .unique
+        .par_iter()
+        .map(|kept| {
+            let group: Vec<&PathBuf> = std::iter::once(kept)
+                .chain(
+                    dedup
+                        .comparisons
+                        .iter()
+                        .filter(|c| c.merged && &c.closest == kept)
+                        .map(|c| &c.frame),
+                )
+                .collect();
+            let step = group.len().div_ceil(SHARPNESS_CANDIDATES);
+            group
+                .into_iter()
+                .step_by(step)
+                .filter_map(|p| sharpness(p).ok().map(|s| (p, s)))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map_or_else(|| kept.clone(), |(p, _)| p.clone())
+        })
+        .collect()
+}
+
+/// Variance of the 4-neighbour Laplacian over the grayscale frame: higher
+/// means crisper edges.
+fn sharpness(path: &Path) -> R<f64> {
+    let gray = image::open(path)?.to_luma8();
+    let (w, h) = gray.dimensions();
+    if w < 3 || h < 3 {
+        return Ok(0.0);
+    }
+    let px = |x: u32, y: u32| gray.get_pixel(x, y)[0] as f64;
+    let (mut sum, mut sum_sq, mut n) = (0.0, 0.0, 0.0);
+    for y in 1..h - 1 {
+        for x in 1..w - 1 {
+            let lap = px(x - 1, y) + px(x + 1, y) + px(x, y - 1) + px(x, y + 1) - 4.0 * px(x, y);
+            sum += lap;
+            sum_sq += lap * lap;
+            n += 1.0;
+        }
+    }
+    let mean = sum / n;
+    Ok(sum_sq / n - mean * mean)
+}
+
+/// Debug output for `--dump-similarity`: one CSV row per dedup decision.
+fn write_similarity_csv(path: &Path, comparisons: &[Comparison]) -> R<()> {
+    let name = |p: &Path| {
+        p.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    };
+    let mut csv = String::from("frame_a,frame_b,similarity,merged\n");
+    for c in comparisons {
+        csv.push_str(&format!(
+            "{},{},{:.4},{}\n",
+            csv_field(&name(&c.closest)),
+            csv_field(&name(&c.frame)),
+            c.similarity,
+            c.merged
+        ));
+    }
+    std::fs::write(path, csv)?;
+    Ok(())
+}
+
+/// One extracted frame as written by --dump-frames.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct FrameEntry {
+    /// 1-based position in the (sorted) frame list
+    frame: usize,
+    timestamp: f64,
+    /// Same fingerprint form as a slide's `hash` in report.json
+    hash: Option<String>,
+    path: PathBuf,
+}
+
+/// Frames with their timestamps: the real ones when ffmpeg recorded them,
+/// otherwise estimated from `--interval`, as for slides.
+fn frame_entries(
+    paths: &[PathBuf],
+    hashes: &[Option<String>],
+    timestamps: &[f64],
+    interval: u64,
+) -> Vec<FrameEntry> {
+    paths
+        .iter()
+        .zip(hashes)
+        .enumerate()
+        .map(|(i, (path, hash))| FrameEntry {
+            frame: i + 1,
+            timestamp: timestamps
+                .get(i)
+                .copied()
+                .unwrap_or(i as f64 * interval as f64),
+            hash: hash.clone(),
+            path: path.clone(),
+        })
+        .collect()
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Advisory only: flag dedup results that suggest a mis-tuned `--threshold`.
+fn threshold_advice(total: usize, unique: usize, duration: f64, threshold: f64) -> Option<String> {
+    if total < 20 {
+        return None;
+    }
+    let ratio = unique as f64 / total as f64;
+    if ratio > 0.8 {
+        return Some(format!(
+            "{} of {} frames kept as unique ({:.0}%); --threshold {:.2} may be too strict, try {:.2}",
+            unique,
+            total,
+            ratio * 100.0,
+            threshold,
+            (threshold - 0.05).max(0.0)
+        ));
+    }
+    // Fewer than one slide per 10 minutes of a long video is suspicious
+    if duration >= 600.0 && (unique as f64) < duration / 600.0 {
+        return Some(format!(
+            "only {} unique frame(s) in {} min of video; --threshold {:.2} may be too loose, try {:.2}",
+            unique,
+            (duration / 60.0) as u64,
+            threshold,
+            (threshold + 0.03).min(1.0)
+        ));
+    }
+    None
 }
 
 // ── Vision LLM (Qwen-VL) ───────────────────────────────────────────────────
@@ -507,6 +2119,20 @@ fn resize_image(path: &Path) -> R<Vec<u8>> {
     Ok(buf.into_inner())
 }
 
+/// Spawn `task` so that it only starts running once it holds one of `sem`'s
+/// permits, which caps how many OCR requests are in flight at once.
+fn spawn_limited<T, F>(sem: &std::sync::Arc<Semaphore>, task: F) -> tokio::task::JoinHandle<T>
+where
+    F: std::future::Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    let sem = sem.clone();
+    tokio::spawn(async move {
+        let _permit = sem.acquire().await.unwrap();
+        task.await
+    })
+}
+
 async fn vision_ocr(
     client: &reqwest::Client,
     path: &Path,
@@ -558,12 +2184,84 @@ async fn vision_ocr(
     }
 }
 
-// ── Text-based dedup ────────────────────────────────────────────────────
+// ── Slide preservation ──────────────────────────────────────────────────────
 
-fn normalize_text(text: &str) -> String {
-    text.split_whitespace()
-        .map(|w| w.to_lowercase())
-        .collect::<Vec<_>>()
+/// Copy a frame into the slides dir (downscaled to `max_width` if wider) and
+/// make sure the result is a decodable image, so a failed or truncated write
+/// never reaches the report. The copy is written atomically, and skipped when
+/// `dest` already holds exactly these bytes, so re-running over the same frames
+/// (e.g. after an interrupted run) rewrites nothing.
+fn preserve_slide(src: &Path, dest: &Path, max_width: Option<u32>) -> R<()> {
+    let bytes = match max_width {
+        Some(max_w) if image::image_dimensions(src)?.0 > max_w => {
+            let img = image::open(src)?;
+            let h = (img.height() as u64 * max_w as u64 / img.width() as u64).max(1) as u32;
+            let mut buf = Cursor::new(Vec::new());
+            img.resize_exact(max_w, h, imageops::FilterType::Lanczos3)
+                .write_to(&mut buf, image::ImageFormat::from_path(dest)?)?;
+            buf.into_inner()
+        }
+        _ => std::fs::read(src)?,
+    };
+    let up_to_date = std::fs::metadata(dest).is_ok_and(|m| m.len() == bytes.len() as u64)
+        && std::fs::read(dest).is_ok_and(|existing| existing == bytes);
+    if !up_to_date {
+        write_atomic(dest, |out| Ok(out.write_all(&bytes)?))?;
+    }
+    validate_slide_image(dest)?;
+    Ok(())
+}
+
+/// Delete slide images in `dir` that the report won't reference, e.g. left by
+/// an earlier run into the same output directory that kept more slides.
+fn remove_stale_slides(dir: &Path, slides: &[SlideData]) {
+    let keep: std::collections::HashSet<&Path> =
+        slides.iter().map(|s| s.image_path.as_path()).collect();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|e| e.path()) {
+        let is_slide = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with("slide_"));
+        if is_slide && !keep.contains(path.as_path()) {
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+}
+
+/// Fail before writing the report if any slide it would link is missing or
+/// unreadable, listing every bad file rather than just the first. Only the
+/// image headers are read: preserve_slide already decoded each one in full.
+fn verify_slide_images(slides: &[SlideData]) -> R<()> {
+    let bad: Vec<String> = slides
+        .par_iter()
+        .filter(|s| !image::image_dimensions(&s.image_path).is_ok_and(|(w, h)| w > 0 && h > 0))
+        .map(|s| s.image_path.display().to_string())
+        .collect();
+    if bad.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("missing or unreadable slide images: {}", bad.join(", ")).into())
+    }
+}
+
+fn validate_slide_image(path: &Path) -> R<(u32, u32)> {
+    let img = image::open(path)?;
+    let (w, h) = (img.width(), img.height());
+    if w == 0 || h == 0 {
+        return Err(format!("{} has zero dimensions", path.display()).into());
+    }
+    Ok((w, h))
+}
+
+// ── Text-based dedup ────────────────────────────────────────────────────
+
+fn normalize_text(text: &str) -> String {
+    text.split_whitespace()
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
         .join(" ")
 }
 
@@ -593,7 +2291,7 @@ fn text_similarity(a: &str, b: &str) -> f64 {
     intersection as f64 / union as f64
 }
 
-fn dedup_by_text(slides: Vec<SlideData>) -> Vec<SlideData> {
+fn dedup_by_text(slides: Vec<SlideData>, threshold: f64) -> Vec<SlideData> {
     let mut unique: Vec<SlideData> = Vec::new();
 
     for slide in slides {
@@ -606,7 +2304,7 @@ fn dedup_by_text(slides: Vec<SlideData>) -> Vec<SlideData> {
         // Find matching existing slide by text similarity
         let match_idx = unique
             .iter()
-            .position(|existing| text_similarity(&existing.text, &slide.text) > 0.6);
+            .position(|existing| text_similarity(&existing.text, &slide.text) > threshold);
 
         match match_idx {
             Some(idx) => {
@@ -624,9 +2322,38 @@ fn dedup_by_text(slides: Vec<SlideData>) -> Vec<SlideData> {
     unique
 }
 
+/// Down-select to `max` slides spread evenly over the deck: always the first
+/// and last, the rest at equal index steps between. Deterministic and keeps
+/// chronological order; dropped slides' images are deleted.
+fn cap_slides(slides: Vec<SlideData>, max: usize) -> Vec<SlideData> {
+    let n = slides.len();
+    if n <= max {
+        return slides;
+    }
+    let keep: std::collections::BTreeSet<usize> = match max {
+        0 => Default::default(),
+        1 => [0].into(),
+        _ => (0..max)
+            .map(|i| (i * (n - 1) + (max - 1) / 2) / (max - 1))
+            .collect(),
+    };
+    slides
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, slide)| {
+            if keep.contains(&i) {
+                Some(slide)
+            } else {
+                let _ = std::fs::remove_file(&slide.image_path);
+                None
+            }
+        })
+        .collect()
+}
+
 // ── Training data collection ────────────────────────────────────────────
 
-fn save_training_data(labels: &[(PathBuf, String)]) {
+fn save_training_data(labels: &[(PathBuf, String)], progress: &Progress) {
     let dir = dirs::data_local_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join("yt-sl")
@@ -649,10 +2376,15 @@ fn save_training_data(labels: &[(PathBuf, String)]) {
     let mut count = 0;
     for (image_path, label) in labels {
         let abs = std::fs::canonicalize(image_path).unwrap_or_else(|_| image_path.clone());
+        // JSON can't hold a non-UTF-8 path, and a lossy one points at no file
+        let Some(image) = abs.to_str() else {
+            eprintln!("  training: skipping non-UTF-8 path {}", abs.display());
+            continue;
+        };
         let entry = serde_json::json!({
             "input": TRAINING_PROMPT,
             "output": label,
-            "image": abs.to_str().unwrap_or("")
+            "image": image
         });
         if writeln!(file, "{}", entry).is_ok() {
             count += 1;
@@ -665,16 +2397,84 @@ fn save_training_data(labels: &[(PathBuf, String)]) {
             .map(|s| s.lines().count())
             .unwrap_or(0);
 
-        eprintln!("  training: +{} labels saved (total: {})", count, total);
+        progress.detail(&format!(
+            "  training: +{} labels saved (total: {})",
+            count, total
+        ));
 
         if total < 500 {
-            eprintln!("  -> collect ~{} more labels before training", 500 - total);
+            progress.detail(&format!(
+                "  -> collect ~{} more labels before training",
+                500 - total
+            ));
         } else {
-            eprintln!("  -> ready to train! run: oumi train -c classifier/src/train.yaml");
+            progress.detail("  -> ready to train! run: oumi train -c classifier/src/train.yaml");
         }
     }
 }
 
+// ── Subtitles ───────────────────────────────────────────────────────────────
+
+/// Parse WebVTT or SRT cues into transcript segments. Inline tags are
+/// stripped, and lines repeated from the previous cue (YouTube's rolling
+/// auto-captions) are dropped so each phrase appears once.
+fn parse_subtitles(data: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut last_line = String::new();
+    let mut lines = data.lines().peekable();
+    while let Some(line) = lines.next() {
+        let Some((start, end)) = line.split_once("-->") else {
+            continue;
+        };
+        let (Some(start), Some(end)) = (
+            parse_cue_time(start),
+            end.split_whitespace().next().and_then(parse_cue_time),
+        ) else {
+            continue;
+        };
+        let mut text = Vec::new();
+        while let Some(l) = lines.next_if(|l| !l.trim().is_empty()) {
+            let clean = strip_tags(l).trim().to_string();
+            if !clean.is_empty() && clean != last_line {
+                last_line = clean.clone();
+                text.push(clean);
+            }
+        }
+        if !text.is_empty() {
+            segments.push(Segment {
+                start,
+                end,
+                text: text.join(" "),
+            });
+        }
+    }
+    segments
+}
+
+/// `HH:MM:SS.mmm`, `MM:SS.mmm` or SRT's `HH:MM:SS,mmm`, in seconds.
+fn parse_cue_time(s: &str) -> Option<f64> {
+    let s = s.trim().replace(',', ".");
+    let mut secs = 0.0;
+    for part in s.split(':') {
+        secs = secs * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some(secs)
+}
+
+fn strip_tags(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut in_tag = false;
+    for c in s.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
 // ── Segment assignment ──────────────────────────────────────────────────────
 
 fn assign_segments(slides: &mut [SlideData], segments: &[Segment], interval: f64) {
@@ -695,55 +2495,2088 @@ fn assign_segments(slides: &mut [SlideData], segments: &[Segment], interval: f64
     }
 }
 
+// ── Run comparison ──────────────────────────────────────────────────────────
+
+/// Visual similarity above which a slide counts as an edited version of a
+/// baseline slide rather than a new one.
+const CHANGED_SIMILARITY: f64 = 0.75;
+
+/// Label each slide against the closest baseline slide by image hash: a visual
+/// match (`threshold`, as in dedup) with the same text is unchanged, a looser
+/// match or different text is changed, anything else is new. Baseline slides
+/// without a stored hash (reports from older runs) can't be matched.
+fn mark_changes(
+    slides: &mut [SlideData],
+    baseline: &[SlideData],
+    threshold: f64,
+    hasher: &dyn FrameHasher,
+) {
+    let base: Vec<(&str, &str)> = baseline
+        .iter()
+        .filter_map(|b| Some((b.hash.as_deref()?, b.text.as_str())))
+        .collect();
+    for slide in slides {
+        let Some(hash) = slide.hash.as_deref() else {
+            continue;
+        };
+        let closest = base
+            .iter()
+            .map(|&(h, text)| (hasher.similarity(h, hash), text))
+            .max_by(|a, b| a.0.total_cmp(&b.0));
+        slide.change = Some(match closest {
+            Some((sim, text)) if sim >= threshold && text_similarity(&slide.text, text) >= 0.9 => {
+                SlideChange::Unchanged
+            }
+            Some((sim, _)) if sim >= CHANGED_SIMILARITY => SlideChange::Changed,
+            _ => SlideChange::New,
+        });
+    }
+}
+
+fn change_summary(slides: &[SlideData], labels: &Labels) -> Option<String> {
+    let count = |c: SlideChange| slides.iter().filter(|s| s.change == Some(c)).count();
+    if slides.iter().all(|s| s.change.is_none()) {
+        return None;
+    }
+    Some(format!(
+        "{}: {} {}, {} {}, {} {}",
+        labels.diff[0],
+        count(SlideChange::New),
+        labels.diff[1],
+        count(SlideChange::Changed),
+        labels.diff[2],
+        count(SlideChange::Unchanged),
+        labels.diff[3]
+    ))
+}
+
 // ── Markdown generation ─────────────────────────────────────────────────────
 
+#[derive(Clone, Copy, PartialEq, Debug, ValueEnum)]
+enum ReportLanguage {
+    En,
+    Es,
+}
+
+impl ReportLanguage {
+    fn labels(self) -> &'static Labels {
+        match self {
+            ReportLanguage::En => &LABELS_EN,
+            ReportLanguage::Es => &LABELS_ES,
+        }
+    }
+}
+
+/// The report's fixed wording, one catalog per `--report-language`.
+struct Labels {
+    source: &'static str,
+    uploader: &'static str,
+    published: &'static str,
+    views: &'static str,
+    full_transcript: &'static str,
+    click_to_expand: &'static str,
+    slides: &'static str,
+    slide: &'static str,
+    text: &'static str,
+    speaker_notes: &'static str,
+    no_slides: &'static str,
+    /// Points the empty-report guidance at the settings table
+    see_settings: &'static str,
+    things_to_try: &'static str,
+    suggestions: [&'static str; 4],
+    /// "{label}: N examined, N kept, N merged (N% merge ratio)"
+    dedup: [&'static str; 5],
+    /// "{label}: N new, N changed, N unchanged"
+    diff: [&'static str; 4],
+    script: &'static str,
+    scripts: &'static str,
+    only: &'static str,
+    settings: &'static str,
+    changed_from_defaults: &'static str,
+    /// Settings table header: setting, value, default
+    settings_columns: [&'static str; 3],
+}
+
+const LABELS_EN: Labels = Labels {
+    source: "Source",
+    uploader: "Uploader",
+    published: "Published",
+    views: "Views",
+    full_transcript: "Full Transcript",
+    click_to_expand: "Click to expand",
+    slides: "Slides",
+    slide: "Slide",
+    text: "Text",
+    speaker_notes: "Speaker Notes",
+    no_slides: "No slides were detected in this video.",
+    see_settings: "The settings this run used are listed at the end of this report.",
+    things_to_try: "Things to try",
+    suggestions: [
+        "Check `ocr_errors` in report.json; a vision API outage drops every slide",
+        "Raise `--threshold` so fewer frames are merged before OCR",
+        "Use a shorter `--interval` if slides are only shown briefly",
+        "Try a larger `--model` if frames are being classified as NOT_SLIDE",
+    ],
+    dedup: ["Dedup", "frames examined", "kept", "merged", "merge ratio"],
+    diff: ["Compared with previous run", "new", "changed", "unchanged"],
+    script: "Script",
+    scripts: "Scripts",
+    only: "only",
+    settings: "Settings",
+    changed_from_defaults: "changed from defaults",
+    settings_columns: ["Setting", "Value", "Default"],
+};
+
+const LABELS_ES: Labels = Labels {
+    source: "Fuente",
+    uploader: "Autor",
+    published: "Publicado",
+    views: "Visualizaciones",
+    full_transcript: "Transcripción completa",
+    click_to_expand: "Haz clic para expandir",
+    slides: "Diapositivas",
+    slide: "Diapositiva",
+    text: "Texto",
+    speaker_notes: "Notas del orador",
+    no_slides: "No se detectaron diapositivas en este vídeo.",
+    see_settings: "Los ajustes de esta ejecución aparecen al final del informe.",
+    things_to_try: "Qué probar",
+    suggestions: [
+        "Revisa `ocr_errors` en report.json; una caída de la API de visión descarta todas las diapositivas",
+        "Sube `--threshold` para que se fusionen menos fotogramas antes del OCR",
+        "Usa un `--interval` más corto si las diapositivas se muestran poco tiempo",
+        "Prueba un `--model` más grande si los fotogramas se clasifican como NOT_SLIDE",
+    ],
+    dedup: ["Deduplicación", "fotogramas examinados", "conservados", "fusionados", "de fusión"],
+    diff: ["Comparado con la ejecución anterior", "nueva", "modificada", "sin cambios"],
+    script: "Escritura",
+    scripts: "Escrituras",
+    only: "solamente",
+    settings: "Ajustes",
+    changed_from_defaults: "distintos de los predeterminados",
+    settings_columns: ["Ajuste", "Valor", "Predeterminado"],
+};
+
+/// Report-level metadata shown in the header.
+struct ReportMeta<'a> {
+    title: &'a str,
+    url: &'a str,
+    video: &'a VideoInfo,
+    /// Estimated video length in seconds
+    duration: f64,
+    front_matter: bool,
+    /// Inline slide images as data URLs instead of linking slides/<name>
+    embed_images: bool,
+    labels: &'static Labels,
+    dedup: DedupStats,
+    /// The full settings block at the end of the report
+    run_settings: &'a [Setting],
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, ValueEnum)]
+enum ReportFormat {
+    Md,
+    Html,
+}
+
+impl ReportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Md => "md",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+
+fn render_report(
+    format: ReportFormat,
+    meta: &ReportMeta,
+    transcript: &str,
+    slides: &[SlideData],
+    out: &mut dyn Write,
+) -> R<()> {
+    match format {
+        ReportFormat::Md => generate_markdown(meta, transcript, slides, out),
+        ReportFormat::Html => generate_html(meta, transcript, slides, out),
+    }
+}
+
+/// Write `<dir>/<name>.<ext>` for every format. A format that fails doesn't stop
+/// the rest; returns the files written and one message per failure.
+fn write_reports(
+    formats: &[ReportFormat],
+    name: &str,
+    meta: &ReportMeta,
+    transcript: &str,
+    slides: &[SlideData],
+    dir: &Path,
+) -> (Vec<PathBuf>, Vec<String>) {
+    let mut written = Vec::new();
+    let mut failed = Vec::new();
+    for &format in formats {
+        let path = dir.join(format!(
+            "{}.{}",
+            sanitize_report_name(name),
+            format.extension()
+        ));
+        match write_atomic(&path, |out| {
+            render_report(format, meta, transcript, slides, out)
+        }) {
+            Ok(()) => written.push(path),
+            Err(e) => {
+                eprintln!("  warning: could not write {}: {}", path.display(), e);
+                failed.push(format!("{}: {}", path.display(), e));
+            }
+        }
+    }
+    (written, failed)
+}
+
+/// --report-name as a plain file name: anything but letters, digits, '-', '_' and
+/// '.' becomes '-', and leading dots are dropped, so it can't leave the output
+/// directory or turn into a hidden file.
+fn sanitize_report_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>()
+        .trim_start_matches('.')
+        .to_string()
+}
+
+/// Header fields shown under the title, as (label, value) pairs.
+fn header_details(meta: &ReportMeta) -> Vec<(&'static str, String)> {
+    let (l, v) = (meta.labels, meta.video);
+    [
+        v.uploader.clone().map(|u| (l.uploader, u)),
+        v.upload_date.clone().map(|d| (l.published, d)),
+        v.view_count.map(|n| (l.views, thousands(n))),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Image sources for one batch of slides: data URLs (encoded in parallel) with
+/// --embed-images, otherwise `slides/<name>` links.
+fn slide_image_sources(chunk: &[SlideData], embed: bool) -> R<Vec<String>> {
+    if embed {
+        return chunk
+            .par_iter()
+            .map(|slide| image_data_url(&slide.image_path))
+            .collect();
+    }
+    Ok(chunk
+        .iter()
+        .map(|slide| {
+            // Slide files are always named slide_NNNN.<ext> by the run itself
+            let name = slide.image_path.file_name().unwrap_or_default();
+            format!("slides/{}", name.to_string_lossy())
+        })
+        .collect())
+}
+
+/// Slides are written out a batch at a time. With --embed-images each batch's
+/// images are encoded together, so only one batch of data URLs is ever held in
+/// memory.
+fn embed_batch_size(meta: &ReportMeta, slides: usize) -> usize {
+    if meta.embed_images {
+        rayon::current_num_threads().max(1)
+    } else {
+        slides.max(1)
+    }
+}
+
+/// Heading suffix for `--diff-against` results; unchanged slides get none.
+fn change_marker(slide: &SlideData, labels: &Labels) -> String {
+    match slide.change {
+        Some(SlideChange::New) => format!(" — {}", labels.diff[1]),
+        Some(SlideChange::Changed) => format!(" — {}", labels.diff[2]),
+        Some(SlideChange::Unchanged) | None => String::new(),
+    }
+}
+
+/// "Settings (N changed from defaults)"
+fn settings_summary(meta: &ReportMeta) -> String {
+    let l = meta.labels;
+    let changed = meta.run_settings.iter().filter(|s| s.changed).count();
+    format!("{} ({} {})", l.settings, changed, l.changed_from_defaults)
+}
+
+/// A value that can't break out of its markdown table cell.
+fn md_table_cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+/// The closing summary lines: change counts, script coverage, dedup stats.
+fn footer_lines(meta: &ReportMeta, slides: &[SlideData]) -> Vec<String> {
+    let l = meta.labels;
+    let mut lines: Vec<String> = [change_summary(slides, l), script_coverage(slides, l)]
+        .into_iter()
+        .flatten()
+        .collect();
+    let d = meta.dedup;
+    if d.frames_examined > 0 {
+        lines.push(format!(
+            "{}: {} {}, {} {}, {} {} ({:.0}% {})",
+            l.dedup[0],
+            d.frames_examined,
+            l.dedup[1],
+            d.slides_kept,
+            l.dedup[2],
+            d.frames_merged,
+            l.dedup[3],
+            d.merge_ratio * 100.0,
+            l.dedup[4]
+        ));
+    }
+    lines
+}
+
 fn generate_markdown(
-    title: &str,
-    url: &str,
+    meta: &ReportMeta,
     transcript: &str,
     slides: &[SlideData],
-    output: &Path,
+    out: &mut dyn Write,
 ) -> R<()> {
-    let mut md = format!("# {}\n\n", title);
+    let (title, url) = (meta.title, meta.url);
+    let l = meta.labels;
+    let mut md = String::new();
+    if meta.front_matter {
+        md.push_str(&front_matter(meta, slides.len()));
+    }
+    md.push_str(&format!("# {}\n\n", title));
 
     if !url.is_empty() {
-        md.push_str(&format!("**Source:** [{}]({})\n\n", url, url));
+        md.push_str(&format!("**{}:** [{}]({})\n\n", l.source, url, url));
+    }
+
+    let details: Vec<String> = header_details(meta)
+        .into_iter()
+        .map(|(label, value)| format!("**{}:** {}", label, value))
+        .collect();
+    if !details.is_empty() {
+        md.push_str(&details.join(" · "));
+        md.push_str("\n\n");
     }
 
     if !transcript.is_empty() {
-        md.push_str("## Full Transcript\n\n<details>\n<summary>Click to expand</summary>\n\n");
+        md.push_str(&format!(
+            "## {}\n\n<details>\n<summary>{}</summary>\n\n",
+            l.full_transcript, l.click_to_expand
+        ));
         md.push_str(transcript);
         md.push_str("\n\n</details>\n\n");
     }
 
-    md.push_str("## Slides\n\n");
-    for slide in slides {
-        let mins = slide.timestamp as u64 / 60;
-        let secs = slide.timestamp as u64 % 60;
+    md.push_str(&format!("## {}\n\n", l.slides));
+    if slides.is_empty() {
+        md.push_str(&empty_report_guidance(meta));
+    }
+    for chunk in slides.chunks(embed_batch_size(meta, slides.len())) {
+        let sources = slide_image_sources(chunk, meta.embed_images)?;
+        for (slide, src) in chunk.iter().zip(sources) {
+            let mins = slide.timestamp as u64 / 60;
+            let secs = slide.timestamp as u64 % 60;
+            md.push_str(&format!(
+                "### {} {} ({}:{:02}){}\n\n",
+                l.slide,
+                slide.index,
+                mins,
+                secs,
+                change_marker(slide, l)
+            ));
+            md.push_str(&format!(
+                "![{}]({})\n\n",
+                md_escape_alt(&slide_alt_text(slide, l)),
+                src
+            ));
+
+            md.push_str(&format!("#### {}\n\n", l.text));
+            md.push_str(&slide.text);
+            md.push_str("\n\n");
+
+            if !slide.transcript.is_empty() {
+                md.push_str(&format!("#### {}\n\n", l.speaker_notes));
+                md.push_str(&slide.transcript);
+                md.push_str("\n\n");
+            }
+
+            md.push_str("---\n\n");
+        }
+        out.write_all(md.as_bytes())?;
+        md.clear();
+    }
+
+    let footer = footer_lines(meta, slides);
+    for (i, line) in footer.iter().enumerate() {
+        // Blank lines between so each renders as its own paragraph
+        let sep = if i + 1 < footer.len() { "\n\n" } else { "\n" };
+        md.push_str(&format!("*{}*{}", line, sep));
+    }
+
+    if !meta.run_settings.is_empty() {
+        let cols = l.settings_columns;
         md.push_str(&format!(
-            "### Slide {} ({}:{:02})\n\n",
-            slide.index, mins, secs
+            "\n<details>\n<summary>{}</summary>\n\n| {} | {} | {} |\n|---|---|---|\n",
+            settings_summary(meta),
+            cols[0],
+            cols[1],
+            cols[2]
         ));
+        for setting in meta.run_settings {
+            let value = md_table_cell(&setting.value);
+            md.push_str(&format!(
+                "| `{}` | {} | {} |\n",
+                setting.name,
+                if setting.changed {
+                    format!("**{}**", value)
+                } else {
+                    value
+                },
+                setting
+                    .default
+                    .as_deref()
+                    .map_or("—".to_string(), md_table_cell)
+            ));
+        }
+        md.push_str("\n</details>\n");
+    }
 
-        let rel_path = slide.image_path.file_name().unwrap().to_str().unwrap();
-        md.push_str(&format!(
-            "![Slide {}](slides/{})\n\n",
-            slide.index, rel_path
+    out.write_all(md.as_bytes())?;
+    Ok(())
+}
+
+fn generate_html(
+    meta: &ReportMeta,
+    transcript: &str,
+    slides: &[SlideData],
+    out: &mut dyn Write,
+) -> R<()> {
+    let l = meta.labels;
+    let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", html_escape(meta.title)));
+    html.push_str(
+        "<style>body{max-width:60rem;margin:auto;font-family:sans-serif}\
+         img{max-width:100%}pre{white-space:pre-wrap}</style>\n</head>\n<body>\n",
+    );
+    html.push_str(&format!("<h1>{}</h1>\n", html_escape(meta.title)));
+
+    if !meta.url.is_empty() {
+        let url = html_escape(meta.url);
+        html.push_str(&format!(
+            "<p><strong>{}:</strong> <a href=\"{}\">{}</a></p>\n",
+            l.source, url, url
         ));
+    }
+    let details: Vec<String> = header_details(meta)
+        .into_iter()
+        .map(|(label, value)| format!("<strong>{}:</strong> {}", label, html_escape(&value)))
+        .collect();
+    if !details.is_empty() {
+        html.push_str(&format!("<p>{}</p>\n", details.join(" · ")));
+    }
 
-        md.push_str("#### Text\n\n");
-        md.push_str(&slide.text);
-        md.push_str("\n\n");
+    if !transcript.is_empty() {
+        html.push_str(&format!(
+            "<h2>{}</h2>\n<details>\n<summary>{}</summary>\n<p>{}</p>\n</details>\n",
+            l.full_transcript,
+            l.click_to_expand,
+            html_escape(transcript)
+        ));
+    }
 
-        if !slide.transcript.is_empty() {
-            md.push_str("#### Speaker Notes\n\n");
-            md.push_str(&slide.transcript);
-            md.push_str("\n\n");
+    html.push_str(&format!("<h2>{}</h2>\n", l.slides));
+    if slides.is_empty() {
+        html.push_str(&format!("<p><em>{}</em></p>\n", l.no_slides));
+        if !meta.run_settings.is_empty() {
+            html.push_str(&format!("<p>{}</p>\n", l.see_settings));
         }
-
-        md.push_str("---\n\n");
+        html.push_str(&format!("<p>{}:</p>\n<ul>\n", l.things_to_try));
+        for suggestion in l.suggestions {
+            html.push_str(&format!("<li>{}</li>\n", html_inline_code(suggestion)));
+        }
+        html.push_str("</ul>\n");
+    }
+    for chunk in slides.chunks(embed_batch_size(meta, slides.len())) {
+        let sources = slide_image_sources(chunk, meta.embed_images)?;
+        for (slide, src) in chunk.iter().zip(sources) {
+            let mins = slide.timestamp as u64 / 60;
+            let secs = slide.timestamp as u64 % 60;
+            html.push_str(&format!(
+                "<section>\n<h3>{} {} ({}:{:02}){}</h3>\n",
+                l.slide,
+                slide.index,
+                mins,
+                secs,
+                change_marker(slide, l)
+            ));
+            html.push_str(&format!(
+                "<img src=\"{}\" alt=\"{}\">\n",
+                src,
+                html_escape(&slide_alt_text(slide, l))
+            ));
+            html.push_str(&format!(
+                "<h4>{}</h4>\n<pre>{}</pre>\n",
+                l.text,
+                html_escape(&slide.text)
+            ));
+            if !slide.transcript.is_empty() {
+                html.push_str(&format!(
+                    "<h4>{}</h4>\n<p>{}</p>\n",
+                    l.speaker_notes,
+                    html_escape(&slide.transcript)
+                ));
+            }
+            html.push_str("</section>\n<hr>\n");
+        }
+        out.write_all(html.as_bytes())?;
+        html.clear();
     }
 
-    std::fs::write(output, md)?;
+    for line in footer_lines(meta, slides) {
+        html.push_str(&format!("<p><em>{}</em></p>\n", html_escape(&line)));
+    }
+    if !meta.run_settings.is_empty() {
+        let cols = l.settings_columns;
+        html.push_str(&format!(
+            "<details>\n<summary>{}</summary>\n<table>\n<tr><th>{}</th><th>{}</th><th>{}</th></tr>\n",
+            settings_summary(meta),
+            cols[0],
+            cols[1],
+            cols[2]
+        ));
+        for setting in meta.run_settings {
+            let value = html_escape(&setting.value);
+            html.push_str(&format!(
+                "<tr><td><code>{}</code></td><td>{}</td><td>{}</td></tr>\n",
+                setting.name,
+                if setting.changed {
+                    format!("<strong>{}</strong>", value)
+                } else {
+                    value
+                },
+                setting
+                    .default
+                    .as_deref()
+                    .map_or("—".to_string(), html_escape)
+            ));
+        }
+        html.push_str("</table>\n</details>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    out.write_all(html.as_bytes())?;
     Ok(())
 }
+
+fn html_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escape, turning the catalog's `backticked` flags into <code> spans.
+fn html_inline_code(s: &str) -> String {
+    html_escape(s)
+        .split('`')
+        .enumerate()
+        .map(|(i, part)| {
+            if i % 2 == 1 {
+                format!("<code>{}</code>", part)
+            } else {
+                part.to_string()
+            }
+        })
+        .collect()
+}
+
+fn image_data_url(path: &Path) -> R<String> {
+    let mime = match path.extension().and_then(|e| e.to_str()) {
+        Some("png") => "image/png",
+        _ => "image/jpeg",
+    };
+    let b64 = general_purpose::STANDARD.encode(std::fs::read(path)?);
+    Ok(format!("data:{};base64,{}", mime, b64))
+}
+
+/// Alt text for a slide image: its first line of OCR text, so screen readers and
+/// link previews get more than "Slide N". Plain text; each format escapes it.
+fn slide_alt_text(slide: &SlideData, labels: &Labels) -> String {
+    const MAX_CHARS: usize = 100;
+    let Some(line) = slide
+        .text
+        .lines()
+        .map(|l| l.trim().trim_start_matches('#').trim())
+        .find(|l| !l.is_empty())
+    else {
+        return format!("{} {}", labels.slide, slide.index);
+    };
+    let mut alt = String::new();
+    for (n, c) in line.chars().enumerate() {
+        if n == MAX_CHARS {
+            alt.push('…');
+            break;
+        }
+        alt.push(c);
+    }
+    alt
+}
+
+/// Backslash-escape what would otherwise end or format a Markdown alt text.
+fn md_escape_alt(alt: &str) -> String {
+    let mut out = String::with_capacity(alt.len());
+    for c in alt.chars() {
+        if matches!(c, '[' | ']' | '\\' | '*' | '_' | '`') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Writing system of a letter, by Unicode block. Kana marks Japanese; Han alone
+/// can't tell Chinese from Japanese, so it gets its own label.
+fn script_of(c: char) -> Option<&'static str> {
+    if !c.is_alphabetic() {
+        return None;
+    }
+    Some(match c as u32 {
+        0x0000..=0x024F | 0x1E00..=0x1EFF => "Latin",
+        0x0370..=0x03FF => "Greek",
+        0x0400..=0x052F => "Cyrillic",
+        0x0590..=0x05FF => "Hebrew",
+        0x0600..=0x06FF | 0x0750..=0x077F => "Arabic",
+        0x0900..=0x097F => "Devanagari",
+        0x0E00..=0x0E7F => "Thai",
+        0x3040..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9F => "Japanese",
+        0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => "Korean",
+        0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF => "Han",
+        _ => "Other",
+    })
+}
+
+/// Footer line breaking down the OCR text's letters by script, e.g.
+/// "Scripts: Latin 80%, Japanese 20%". Scripts under 1% are left out.
+fn script_coverage(slides: &[SlideData], labels: &Labels) -> Option<String> {
+    let mut counts: std::collections::BTreeMap<&str, usize> = Default::default();
+    for c in slides.iter().flat_map(|s| s.text.chars()) {
+        if let Some(script) = script_of(c) {
+            *counts.entry(script).or_default() += 1;
+        }
+    }
+    let total: usize = counts.values().sum();
+    if total == 0 {
+        return None;
+    }
+    let mut shares: Vec<(&str, f64)> = counts
+        .into_iter()
+        .map(|(script, n)| (script, n as f64 * 100.0 / total as f64))
+        .filter(|&(_, pct)| pct >= 1.0)
+        .collect();
+    shares.sort_by(|a, b| b.1.total_cmp(&a.1));
+    if let [(script, _)] = shares.as_slice() {
+        return Some(format!("{}: {} {}", labels.script, script, labels.only));
+    }
+    let parts: Vec<String> = shares
+        .iter()
+        .map(|(script, pct)| format!("{} {:.0}%", script, pct))
+        .collect();
+    Some(format!("{}: {}", labels.scripts, parts.join(", ")))
+}
+
+fn empty_report_guidance(meta: &ReportMeta) -> String {
+    let l = meta.labels;
+    let mut out = format!("*{}*\n\n", l.no_slides);
+    if !meta.run_settings.is_empty() {
+        out.push_str(&format!("{}\n\n", l.see_settings));
+    }
+    out.push_str(&format!("{}:\n\n", l.things_to_try));
+    for suggestion in l.suggestions {
+        out.push_str(&format!("- {}\n", suggestion));
+    }
+    out.push('\n');
+    out
+}
+
+fn front_matter(meta: &ReportMeta, slide_count: usize) -> String {
+    let mut fm = String::from("---\n");
+    fm.push_str(&format!("title: {}\n", yaml_quote(meta.title)));
+    fm.push_str(&format!("date: {}\n", today_utc()));
+    if !meta.url.is_empty() {
+        fm.push_str(&format!("source: {}\n", yaml_quote(meta.url)));
+    }
+    fm.push_str(&format!("duration: {}\n", meta.duration.round() as u64));
+    fm.push_str(&format!("slide_count: {}\n", slide_count));
+    fm.push_str("---\n\n");
+    fm
+}
+
+/// Double-quoted YAML scalar, safe for titles containing `:`, `#`, quotes, etc.
+fn yaml_quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Today's date as YYYY-MM-DD (UTC), without pulling in a date crate.
+fn today_utc() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    // Civil-from-days (Howard Hinnant's algorithm)
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta<'a>(title: &'a str, url: &'a str, video: &'a VideoInfo) -> ReportMeta<'a> {
+        ReportMeta {
+            title,
+            url,
+            video,
+            duration: 0.0,
+            front_matter: false,
+            embed_images: false,
+            labels: &LABELS_EN,
+            dedup: DedupStats::default(),
+            run_settings: &[],
+        }
+    }
+
+    #[test]
+    fn yaml_quote_escapes_quotes_backslashes_and_controls() {
+        assert_eq!(yaml_quote("Rust: a talk"), r#""Rust: a talk""#);
+        assert_eq!(yaml_quote(r#"say "hi" \o/"#), r#""say \"hi\" \\o/""#);
+        assert_eq!(yaml_quote("a\nb\tc\u{7}"), r#""a\nb\tc\u0007""#);
+    }
+
+    #[test]
+    fn front_matter_quotes_title_and_skips_empty_source() {
+        let video = VideoInfo::default();
+        let mut m = meta("Intro: #1", "", &video);
+        m.duration = 89.6;
+        let fm = front_matter(&m, 3);
+        assert!(fm.starts_with("---\ntitle: \"Intro: #1\"\ndate: "));
+        assert!(fm.ends_with("duration: 90\nslide_count: 3\n---\n\n"));
+        assert!(!fm.contains("source:"));
+
+        let m = meta("t", "https://youtu.be/x?a=1", &video);
+        assert!(front_matter(&m, 0).contains("source: \"https://youtu.be/x?a=1\"\n"));
+    }
+
+    /// A 64x48 slide-like frame: bright background, a mid-grey block, no dark edges.
+    fn slide_image() -> image::GrayImage {
+        image::GrayImage::from_fn(64, 48, |x, y| {
+            image::Luma([if (16..40).contains(&x) && (8..24).contains(&y) {
+                90
+            } else {
+                220
+            }])
+        })
+    }
+
+    #[test]
+    fn letterbox_bars_dont_change_the_hash() {
+        let slide = slide_image();
+        let boxed = image::GrayImage::from_fn(64, 64, |x, y| {
+            if (8..56).contains(&y) {
+                *slide.get_pixel(x, y - 8)
+            } else {
+                image::Luma([0])
+            }
+        });
+        assert_eq!(trim_letterbox(boxed.clone()).dimensions(), (64, 48));
+        let hash =
+            |img: image::GrayImage| AverageHasher.hash(&image::DynamicImage::ImageLuma8(img));
+        assert_eq!(hash(boxed), hash(slide));
+    }
+
+    #[test]
+    fn one_sided_dark_margin_is_kept() {
+        let slide = slide_image();
+        let top_bar = image::GrayImage::from_fn(64, 48, |x, y| {
+            if y < 8 {
+                image::Luma([0])
+            } else {
+                *slide.get_pixel(x, y)
+            }
+        });
+        assert_eq!(trim_letterbox(top_bar).dimensions(), (64, 48));
+    }
+
+    #[test]
+    fn parse_cue_time_reads_vtt_and_srt_forms() {
+        assert_eq!(parse_cue_time("01:02:03.500"), Some(3723.5));
+        assert_eq!(parse_cue_time(" 02:03.250 "), Some(123.25));
+        assert_eq!(parse_cue_time("00:00:01,200"), Some(1.2));
+        assert_eq!(parse_cue_time("1:xx"), None);
+    }
+
+    #[test]
+    fn strip_tags_keeps_only_text() {
+        assert_eq!(
+            strip_tags("<c.colorE5E5E5>hello</c> <b>world</b>"),
+            "hello world"
+        );
+        assert_eq!(strip_tags("no tags"), "no tags");
+    }
+
+    #[test]
+    fn parse_subtitles_reads_vtt_and_drops_rolling_repeats() {
+        let vtt = "WEBVTT\n\n\
+            00:00:01.000 --> 00:00:03.000 align:start position:0%\n\
+            first <c>line</c>\n\n\
+            00:00:03.000 --> 00:00:05.000\n\
+            first line\n\
+            second line\n\n\
+            00:00:05.000 --> 00:00:06.000\n\
+            \n";
+        let segs = parse_subtitles(vtt);
+        assert_eq!(segs.len(), 2);
+        assert_eq!((segs[0].start, segs[0].end), (1.0, 3.0));
+        assert_eq!(segs[0].text, "first line");
+        assert_eq!(segs[1].text, "second line");
+    }
+
+    #[test]
+    fn parse_subtitles_reads_srt() {
+        let srt = "1\n00:00:00,500 --> 00:00:02,000\nHello\nthere\n\n2\n00:01:00,000 --> 00:01:02,000\nBye\n";
+        let segs = parse_subtitles(srt);
+        assert_eq!(segs.len(), 2);
+        assert_eq!(segs[0].start, 0.5);
+        assert_eq!(segs[0].text, "Hello there");
+        assert_eq!((segs[1].start, segs[1].text.as_str()), (60.0, "Bye"));
+    }
+
+    fn comparison(merged: bool) -> Comparison {
+        Comparison {
+            frame: PathBuf::from("b.jpg"),
+            closest: PathBuf::from("a.jpg"),
+            similarity: 0.5,
+            merged,
+        }
+    }
+
+    #[test]
+    fn dedup_stats_count_kept_and_merged_frames() {
+        let result = DedupResult {
+            unique: vec![PathBuf::from("a.jpg"), PathBuf::from("c.jpg")],
+            comparisons: vec![comparison(true), comparison(false), comparison(true)],
+            hashes: vec![Some(String::new()); 4],
+        };
+        let stats = result.stats();
+        assert_eq!(stats.frames_examined, 4);
+        assert_eq!(stats.slides_kept, 2);
+        assert_eq!(stats.frames_merged, 2);
+        assert_eq!(stats.merge_ratio, 0.5);
+
+        let empty = DedupResult {
+            unique: vec![],
+            comparisons: vec![],
+            hashes: vec![None],
+        };
+        assert_eq!(empty.stats().merge_ratio, 0.0);
+    }
+
+    /// A slide whose image doesn't exist, so the pruning passes' deletes are no-ops.
+    fn slide(index: usize, text: &str) -> SlideData {
+        SlideData {
+            index,
+            timestamp: index as f64 * 5.0,
+            image_path: PathBuf::from(format!("/nonexistent/slide_{:04}.jpg", index)),
+            text: text.to_string(),
+            transcript: String::new(),
+            hash: None,
+            change: None,
+        }
+    }
+
+    #[test]
+    fn dedup_by_text_keeps_the_fuller_duplicate() {
+        let slides = vec![
+            slide(1, "Ownership and borrowing in Rust"),
+            slide(2, "too short"),
+            slide(3, "Ownership and borrowing in Rust explained"),
+            slide(4, "Lifetimes tie references to their scopes"),
+        ];
+        let kept = dedup_by_text(slides, 0.6);
+        let indexes: Vec<usize> = kept.iter().map(|s| s.index).collect();
+        assert_eq!(indexes, [3, 4]);
+    }
+
+    #[test]
+    fn dedup_by_text_respects_the_threshold() {
+        let slides = || {
+            vec![
+                slide(1, "one two three four five six"),
+                slide(2, "one two three four seven eight"),
+            ]
+        };
+        // 4 shared words of 8: similarity 0.5
+        assert_eq!(dedup_by_text(slides(), 0.6).len(), 2);
+        assert_eq!(dedup_by_text(slides(), 0.4).len(), 1);
+    }
+
+    #[test]
+    fn slide_alt_text_uses_the_first_text_line() {
+        let s = slide(2, "\n  ## Agenda  \nmore");
+        assert_eq!(slide_alt_text(&s, &LABELS_EN), "Agenda");
+        assert_eq!(slide_alt_text(&slide(7, " \n"), &LABELS_EN), "Slide 7");
+        assert_eq!(slide_alt_text(&slide(7, ""), &LABELS_ES), "Diapositiva 7");
+
+        let long = "é".repeat(120);
+        let alt = slide_alt_text(&slide(1, &long), &LABELS_EN);
+        assert_eq!(alt.chars().count(), 101);
+        assert!(alt.ends_with("é…"));
+    }
+
+    #[test]
+    fn alt_text_is_escaped_once_per_format() {
+        let s = slide(1, "a [link] & <b>");
+        let alt = slide_alt_text(&s, &LABELS_EN);
+        assert_eq!(md_escape_alt(&alt), r"a \[link\] & <b>");
+        assert_eq!(html_escape(&alt), "a [link] &amp; &lt;b&gt;");
+    }
+
+    /// The flags `validate_detailed` reports for this command line, with
+    /// `--frames frames` added unless it names its own.
+    fn issue_fields(args: &[&str]) -> Vec<&'static str> {
+        let frames: &[&str] = if args.contains(&"--frames") {
+            &[]
+        } else {
+            &["--frames", "frames"]
+        };
+        let argv = ["yt-sl"].iter().chain(frames).chain(args);
+        let args = Args::try_parse_from(argv).unwrap();
+        match args.validate_detailed() {
+            Ok(()) => vec![],
+            Err(issues) => issues.iter().map(|i| i.field).collect(),
+        }
+    }
+
+    #[test]
+    fn validate_detailed_accepts_the_defaults() {
+        assert!(issue_fields(&[]).is_empty());
+        assert!(issue_fields(&["-o", "-", "--embed-images"]).is_empty());
+    }
+
+    #[test]
+    fn validate_detailed_reports_each_invalid_setting() {
+        let cases: [(&[&str], &str); 11] = [
+            (&["--threshold", "1.5"], "--threshold"),
+            (&["--text-threshold=-0.1"], "--text-threshold"),
+            (&["--concurrency", "0"], "--concurrency"),
+            (&["--jobs", "0"], "--jobs"),
+            (&["--max-slides", "0"], "--max-slides"),
+            (&["--max-slide-width", "0"], "--max-slide-width"),
+            (&["--report-name", "..."], "--report-name"),
+            (
+                &["--frames", "no-such-out/x/../slides", "-o", "no-such-out"],
+                "--frames",
+            ),
+            (&["-o", "-"], "--output"),
+            (
+                &["-o", "-", "--embed-images", "--format", "md,html"],
+                "--format",
+            ),
+            (
+                &["-o", "-", "--embed-images", "--progress", "ndjson"],
+                "--output",
+            ),
+        ];
+        for (args, field) in cases {
+            assert_eq!(issue_fields(args), [field], "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn validate_detailed_reports_every_issue_at_once() {
+        let fields = issue_fields(&["--threshold", "2", "--concurrency", "0", "--jobs", "0"]);
+        assert_eq!(fields, ["--threshold", "--concurrency", "--jobs"]);
+        let args = Args::try_parse_from(["yt-sl", "-f", "f", "--threshold", "2"]).unwrap();
+        assert_eq!(
+            args.validate().unwrap_err().to_string(),
+            "--threshold must be between 0.0 and 1.0 (got 2)"
+        );
+    }
+
+    #[test]
+    fn autotune_recommends_the_middle_of_the_plateau() {
+        let (a, b) = (0u64, u64::MAX);
+        // Two slides 12 bits from `a` (similarity 0.8125) and noisy repeats
+        // 2 bits off (0.96875): 2 unique at 0.80, 4 through 0.96, 6 at 0.98
+        let hashes = [
+            a,
+            a ^ 0b11,
+            a ^ (0xfff << 20),
+            b,
+            b ^ 0b11,
+            a ^ (0xfff << 40),
+        ];
+        let paths: Vec<PathBuf> = (0..hashes.len())
+            .map(|i| PathBuf::from(format!("frame_{:04}.jpg", i)))
+            .collect();
+        let hashes: Vec<Option<String>> =
+            hashes.iter().map(|h| Some(format!("{:016x}", h))).collect();
+        let (counts, recommended) = autotune(&paths, &hashes, None, &AverageHasher);
+        let unique: Vec<usize> = counts.iter().map(|&(_, n)| n).collect();
+        assert_eq!(unique, [2, 4, 4, 4, 4, 4, 4, 4, 4, 6]);
+        assert_eq!(recommended, 0.88);
+    }
+
+    #[test]
+    fn format_upload_date_only_rewrites_yt_dlp_dates() {
+        assert_eq!(format_upload_date("20240115"), "2024-01-15");
+        assert_eq!(format_upload_date("2024-01-15"), "2024-01-15");
+        assert_eq!(format_upload_date("2024011"), "2024011");
+        assert_eq!(format_upload_date("2024O115"), "2024O115");
+    }
+
+    #[test]
+    fn thousands_groups_digits() {
+        assert_eq!(thousands(0), "0");
+        assert_eq!(thousands(999), "999");
+        assert_eq!(thousands(1000), "1,000");
+        assert_eq!(thousands(1234567), "1,234,567");
+    }
+
+    #[test]
+    fn cap_slides_keeps_evenly_spaced_slides_in_order() {
+        let deck = |n: usize| (1..=n).map(|i| slide(i, "")).collect::<Vec<_>>();
+        let indexes = |slides: Vec<SlideData>| slides.iter().map(|s| s.index).collect::<Vec<_>>();
+        assert_eq!(indexes(cap_slides(deck(10), 4)), [1, 4, 7, 10]);
+        assert_eq!(indexes(cap_slides(deck(10), 2)), [1, 10]);
+        assert_eq!(indexes(cap_slides(deck(10), 1)), [1]);
+        assert_eq!(indexes(cap_slides(deck(3), 5)), [1, 2, 3]);
+    }
+
+    #[test]
+    fn script_of_classifies_letters_by_block() {
+        assert_eq!(script_of('a'), Some("Latin"));
+        assert_eq!(script_of('é'), Some("Latin"));
+        assert_eq!(script_of('λ'), Some("Greek"));
+        assert_eq!(script_of('Ж'), Some("Cyrillic"));
+        assert_eq!(script_of('あ'), Some("Japanese"));
+        assert_eq!(script_of('カ'), Some("Japanese"));
+        assert_eq!(script_of('한'), Some("Korean"));
+        assert_eq!(script_of('漢'), Some("Han"));
+        assert_eq!(script_of('7'), None);
+        assert_eq!(script_of('-'), None);
+    }
+
+    #[test]
+    fn script_coverage_summarizes_shares() {
+        let latin = [slide(1, "abcd")];
+        assert_eq!(
+            script_coverage(&latin, &LABELS_EN).as_deref(),
+            Some("Script: Latin only")
+        );
+        let mixed = [slide(1, "abc 123"), slide(2, "かな")];
+        assert_eq!(
+            script_coverage(&mixed, &LABELS_EN).as_deref(),
+            Some("Scripts: Latin 60%, Japanese 40%")
+        );
+        assert_eq!(script_coverage(&[slide(1, "42")], &LABELS_EN), None);
+    }
+
+    #[test]
+    fn change_summary_counts_marked_slides() {
+        let mut slides = vec![slide(1, ""), slide(2, ""), slide(3, "")];
+        assert_eq!(change_summary(&slides, &LABELS_EN), None);
+        slides[0].change = Some(SlideChange::New);
+        slides[1].change = Some(SlideChange::Unchanged);
+        slides[2].change = Some(SlideChange::Unchanged);
+        assert_eq!(
+            change_summary(&slides, &LABELS_EN).as_deref(),
+            Some("Compared with previous run: 1 new, 0 changed, 2 unchanged")
+        );
+    }
+
+    #[test]
+    fn mark_changes_compares_hash_and_text() {
+        let text = "Ownership and borrowing in Rust";
+        let with_hash = |index, text, hash: u64| SlideData {
+            hash: Some(format!("{:016x}", hash)),
+            ..slide(index, text)
+        };
+        let baseline = [with_hash(1, text, 0)];
+        let mut slides = [
+            with_hash(1, text, 0b1),
+            with_hash(2, "Lifetimes tie references to scopes", 0xff),
+            with_hash(3, text, u64::MAX),
+            slide(4, text),
+        ];
+        mark_changes(&mut slides, &baseline, 0.9, &AverageHasher);
+        let changes: Vec<Option<SlideChange>> = slides.iter().map(|s| s.change).collect();
+        assert_eq!(
+            changes,
+            [
+                Some(SlideChange::Unchanged),
+                Some(SlideChange::Changed),
+                Some(SlideChange::New),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn sanitize_report_name_keeps_names_inside_the_output_dir() {
+        assert_eq!(
+            sanitize_report_name("talk_2024-v1.final"),
+            "talk_2024-v1.final"
+        );
+        assert_eq!(sanitize_report_name("my talk/../x"), "my-talk-..-x");
+        assert_eq!(sanitize_report_name("../../etc"), "-..-etc");
+        assert_eq!(sanitize_report_name(".hidden"), "hidden");
+        assert_eq!(sanitize_report_name("charlas-é"), "charlas--");
+        assert_eq!(sanitize_report_name(".."), "");
+    }
+
+    fn settings_for(args: &[&str]) -> Vec<Setting> {
+        let matches =
+            Args::command().get_matches_from(["yt-sl", "--frames", "f"].iter().chain(args));
+        effective_settings(&matches)
+    }
+
+    #[test]
+    fn effective_settings_mark_what_differs_from_defaults() {
+        let settings = settings_for(&[
+            "--threshold",
+            "0.9",
+            "--text-threshold",
+            "0.5",
+            "--max-slides",
+            "3",
+        ]);
+        let get = |name: &str| settings.iter().find(|s| s.name == name);
+
+        let threshold = get("--threshold").unwrap();
+        assert_eq!(
+            (threshold.value.as_str(), threshold.changed),
+            ("0.9", false)
+        );
+        let text = get("--text-threshold").unwrap();
+        assert_eq!(text.default.as_deref(), Some("0.6"));
+        assert!(text.changed);
+        let max = get("--max-slides").unwrap();
+        assert_eq!((max.default.as_deref(), max.changed), (None, true));
+        assert_eq!(get("--format").unwrap().value, "md");
+
+        // Unset options without a default, and inputs/diagnostics, are left out
+        assert!(get("--max-slide-width").is_none());
+        assert!(get("--frames").is_none());
+        assert!(get("--progress").is_none());
+    }
+
+    #[test]
+    fn effective_settings_join_list_values() {
+        let settings = settings_for(&["--format", "md,html"]);
+        let format = settings.iter().find(|s| s.name == "--format").unwrap();
+        assert_eq!(format.value, "md,html");
+        assert!(format.changed);
+        assert!(settings_for(&[]).iter().all(|s| !s.changed));
+    }
+
+    #[test]
+    fn empty_report_points_at_the_settings_table_when_there_is_one() {
+        let video = VideoInfo::default();
+        let mut m = meta("t", "", &video);
+        assert!(!empty_report_guidance(&m).contains(LABELS_EN.see_settings));
+        let settings = settings_for(&[]);
+        m.run_settings = &settings;
+        assert!(empty_report_guidance(&m).contains(LABELS_EN.see_settings));
+    }
+
+    #[test]
+    fn write_reports_writes_every_requested_format() {
+        let dir = ScratchDir::create().unwrap();
+        let video = VideoInfo::default();
+        let m = meta("Talk", "", &video);
+        let (written, failed) = write_reports(
+            &[ReportFormat::Md, ReportFormat::Html],
+            "talk",
+            &m,
+            "",
+            &[],
+            &dir.0,
+        );
+        assert!(failed.is_empty(), "{:?}", failed);
+        assert_eq!(written, [dir.0.join("talk.md"), dir.0.join("talk.html")]);
+        let md = std::fs::read_to_string(dir.0.join("talk.md")).unwrap();
+        assert!(md.starts_with("# Talk\n"));
+        let html = std::fs::read_to_string(dir.0.join("talk.html")).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>") && html.contains("<title>Talk</title>"));
+    }
+
+    /// A small non-uniform PNG, so it decodes and hashes like a real frame.
+    fn write_png(path: &Path, w: u32, h: u32) {
+        image::RgbImage::from_fn(w, h, |x, y| image::Rgb([(x * 7) as u8, (y * 5) as u8, 128]))
+            .save(path)
+            .unwrap();
+    }
+
+    fn report_fixture(slides: Vec<SlideData>, settings: Vec<Setting>) -> ExtractionReport {
+        ExtractionReport {
+            title: "Rust talk".to_string(),
+            url: "https://youtu.be/x".to_string(),
+            duration_secs: 600.0,
+            transcript: "hello world".to_string(),
+            video: VideoInfo {
+                uploader: Some("ferris".to_string()),
+                upload_date: Some("2024-01-15".to_string()),
+                view_count: Some(1234),
+            },
+            frames_total: 10,
+            frames_unique: slides.len(),
+            dedup: DedupStats::default(),
+            slides,
+            report_path: PathBuf::from("old-cwd/out/report.md"),
+            timings: Timings::default(),
+            ocr_errors: vec![],
+            output_bytes: 0,
+            settings,
+            documents: vec!["old.md".to_string()],
+        }
+    }
+
+    #[test]
+    fn render_only_matches_the_run_and_keeps_other_reports() {
+        let dir = ScratchDir::create().unwrap();
+        std::fs::create_dir(dir.0.join("slides")).unwrap();
+        write_png(&dir.0.join("slides/slide_0001.png"), 32, 24);
+        std::fs::write(dir.0.join("old.md"), "kept").unwrap();
+
+        let matches = Args::command().get_matches_from([
+            "yt-sl",
+            "--render-only",
+            dir.0.to_str().unwrap(),
+            "--format",
+            "md,html",
+        ]);
+        let args = Args::from_arg_matches(&matches).unwrap();
+        let settings = effective_settings(&matches);
+        let mut stored_slide = slide(1, "Ownership and borrowing in Rust");
+        stored_slide.image_path = PathBuf::from("old-cwd/out/slides/slide_0001.png");
+        let report = report_fixture(vec![stored_slide], settings.clone());
+        std::fs::write(
+            dir.0.join("report.json"),
+            serde_json::to_string(&report).unwrap(),
+        )
+        .unwrap();
+
+        // What the run itself writes for the same slides and settings
+        let expected = ScratchDir::create().unwrap();
+        let mut run_slide = slide(1, "Ownership and borrowing in Rust");
+        run_slide.image_path = dir.0.join("slides/slide_0001.png");
+        let m = ReportMeta {
+            title: &report.title,
+            url: &report.url,
+            video: &report.video,
+            duration: report.duration_secs,
+            front_matter: false,
+            embed_images: false,
+            labels: &LABELS_EN,
+            dedup: report.dedup,
+            run_settings: &settings,
+        };
+        let (_, failed) = write_reports(
+            &args.format,
+            &args.report_name,
+            &m,
+            &report.transcript,
+            &[run_slide],
+            &expected.0,
+        );
+        assert!(failed.is_empty());
+
+        render_only(&args, &dir.0).unwrap();
+        for name in ["report.md", "report.html"] {
+            assert_eq!(
+                std::fs::read(dir.0.join(name)).unwrap(),
+                std::fs::read(expected.0.join(name)).unwrap(),
+                "{}",
+                name
+            );
+        }
+        assert_eq!(
+            std::fs::read_to_string(dir.0.join("old.md")).unwrap(),
+            "kept"
+        );
+        let stored: ExtractionReport =
+            serde_json::from_str(&std::fs::read_to_string(dir.0.join("report.json")).unwrap())
+                .unwrap();
+        assert_eq!(stored.documents, ["old.md", "report.md", "report.html"]);
+    }
+
+    /// Fingerprints a frame by its width alone: any two frames of one size match.
+    struct WidthHasher;
+
+    impl FrameHasher for WidthHasher {
+        fn hash(&self, img: &image::DynamicImage) -> String {
+            format!("width={}", img.width())
+        }
+
+        fn similarity(&self, a: &str, b: &str) -> f64 {
+            if a == b {
+                1.0
+            } else {
+                0.0
+            }
+        }
+    }
+
+    #[test]
+    fn dedup_uses_the_given_hasher() {
+        let dir = ScratchDir::create().unwrap();
+        let gradient = |w: u32, invert: bool| {
+            image::GrayImage::from_fn(w, 24, move |x, _| {
+                let v = (x * 255 / (w - 1)) as u8;
+                image::Luma([if invert { 255 - v } else { v }])
+            })
+        };
+        let paths: Vec<PathBuf> = ["a.png", "b.png", "c.png"]
+            .iter()
+            .map(|n| dir.0.join(n))
+            .collect();
+        gradient(32, false).save(&paths[0]).unwrap();
+        gradient(32, true).save(&paths[1]).unwrap();
+        gradient(48, false).save(&paths[2]).unwrap();
+
+        // Mirrored content differs, a resized copy doesn't
+        let average = dedup_frames(&paths, 0.9, None, &AverageHasher, false);
+        assert_eq!(average.unique, [paths[0].clone(), paths[1].clone()]);
+        // Same width matches, a different width doesn't
+        let by_width = dedup_frames(&paths, 0.9, None, &WidthHasher, false);
+        assert_eq!(by_width.unique, [paths[0].clone(), paths[2].clone()]);
+        assert_eq!(by_width.hashes[2].as_deref(), Some("width=48"));
+    }
+
+    /// A writer the test can read back after handing a clone to the code under test.
+    #[derive(Clone, Default)]
+    struct SharedBuf(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn ndjson_progress_emits_one_json_object_per_line() {
+        let buf = SharedBuf::default();
+        let progress = Progress::with_writer(ProgressMode::Ndjson, Box::new(buf.clone()));
+        let started = std::time::Instant::now();
+        progress.stage(1, "transcript", "No transcript provided, skipping");
+        for done in 0..=3 {
+            progress.item("ocr", done, 3, started);
+        }
+        progress.detail("  not part of the stream");
+        progress.stage(4, "report", "Report: out/report.md");
+
+        let text = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        let records: Vec<serde_json::Value> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        // processed == 0 is skipped
+        assert_eq!(records.len(), 5);
+        assert_eq!(records[0]["stage"], "transcript");
+        assert_eq!(records[0]["eta_secs"], serde_json::Value::Null);
+        assert_eq!(records[3]["processed"], 3);
+        assert_eq!(records[3]["percentage"], 1.0);
+        assert!(records[3]["eta_secs"].is_number());
+        assert_eq!(records[4]["stage"], "report");
+    }
+
+    #[test]
+    fn text_and_quiet_progress_write_no_records() {
+        for mode in [ProgressMode::Text, ProgressMode::None] {
+            let buf = SharedBuf::default();
+            let progress = Progress::with_writer(mode, Box::new(buf.clone()));
+            progress.item("ocr", 1, 2, std::time::Instant::now());
+            assert!(buf.0.lock().unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn single_format_frames_keeps_the_extraction_timestamps_match() {
+        let frames = |names: &[&str]| names.iter().map(PathBuf::from).collect::<Vec<_>>();
+        let mixed = frames(&[
+            "f/frame_0001.jpg",
+            "f/frame_0001.png",
+            "f/frame_0002.jpg",
+            "f/frame_0002.png",
+            "f/frame_0003.jpg",
+        ]);
+        // No timestamps.txt: most frames wins
+        assert_eq!(
+            single_format_frames(mixed.clone(), 0),
+            frames(&["f/frame_0001.jpg", "f/frame_0002.jpg", "f/frame_0003.jpg"])
+        );
+        // timestamps.txt came from the png extraction
+        assert_eq!(
+            single_format_frames(mixed.clone(), 2),
+            frames(&["f/frame_0001.png", "f/frame_0002.png"])
+        );
+        // Matches neither: most frames again
+        assert_eq!(single_format_frames(mixed, 7).len(), 3);
+        let jpgs = frames(&["f/frame_0001.jpg", "f/frame_0002.jpg"]);
+        assert_eq!(single_format_frames(jpgs.clone(), 9), jpgs);
+    }
+
+    #[test]
+    fn projected_remaining_scales_measured_throughput() {
+        assert_eq!(projected_remaining(12.0, 0, 10), 0.0);
+        assert_eq!(projected_remaining(12.0, 3, 10), 28.0);
+        assert_eq!(projected_remaining(40.0, 10, 10), 0.0);
+        assert_eq!(projected_remaining(40.0, 12, 10), 0.0);
+    }
+
+    #[test]
+    fn human_duration_picks_units() {
+        assert_eq!(human_duration(42.4), "42s");
+        assert_eq!(human_duration(185.0), "3m05s");
+        assert_eq!(human_duration(3720.0), "1h02m");
+    }
+
+    #[test]
+    fn extraction_report_round_trips_through_json() {
+        let mut first = slide(1, "Ownership and borrowing in Rust");
+        first.hash = Some("0000000f0f0f0f00".to_string());
+        first.change = Some(SlideChange::Changed);
+        first.transcript = "so today we talk about ownership".to_string();
+        let mut report = report_fixture(vec![first, slide(2, "Lifetimes")], settings_for(&[]));
+        report.dedup = DedupStats {
+            frames_examined: 10,
+            slides_kept: 2,
+            frames_merged: 8,
+            merge_ratio: 0.8,
+        };
+        report.timings = Timings {
+            dedup_secs: 1.5,
+            ocr_secs: 20.25,
+            total_secs: 22.0,
+        };
+        report.ocr_errors = vec!["frame_0003.jpg: timeout".to_string()];
+        report.output_bytes = 12_345;
+
+        let json = serde_json::to_string_pretty(&report).unwrap();
+        let back: ExtractionReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, report);
+    }
+
+    #[test]
+    fn frame_entries_list_kept_merged_and_unhashable_frames() {
+        let paths: Vec<PathBuf> = (1..=4)
+            .map(|i| PathBuf::from(format!("frames/frame_{:04}.jpg", i)))
+            .collect();
+        let hashes =
+            [Some(0u64), Some(0b1), None, Some(u64::MAX)].map(|h| h.map(|h| format!("{:016x}", h)));
+        let dedup = dedup_hashes(&paths, &hashes, None, 0.9, &AverageHasher);
+        assert_eq!(dedup.unique, [paths[0].clone(), paths[3].clone()]);
+
+        // Only two real timestamps: the rest fall back to frame index * interval
+        let entries = frame_entries(&paths, &dedup.hashes, &[0.0, 4.5], 5);
+        let summary: Vec<(usize, f64, Option<&str>)> = entries
+            .iter()
+            .map(|e| (e.frame, e.timestamp, e.hash.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (1, 0.0, Some("0000000000000000")),
+                (2, 4.5, Some("0000000000000001")),
+                (3, 10.0, None),
+                (4, 15.0, Some("ffffffffffffffff")),
+            ]
+        );
+        let json = serde_json::to_string(&entries).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<FrameEntry>>(&json).unwrap(),
+            entries
+        );
+    }
+
+    #[test]
+    fn preserve_slide_rejects_a_truncated_image() {
+        let dir = ScratchDir::create().unwrap();
+        let src = dir.0.join("frame_0001.png");
+        write_png(&src, 64, 48);
+        let bytes = std::fs::read(&src).unwrap();
+        std::fs::write(&src, &bytes[..bytes.len() / 2]).unwrap();
+        let dest = dir.0.join("slide_0001.png");
+        assert!(preserve_slide(&src, &dest, None).is_err());
+        assert!(preserve_slide(&src, &dest, Some(32)).is_err());
+        std::fs::write(&src, b"not an image").unwrap();
+        assert!(preserve_slide(&src, &dest, None).is_err());
+    }
+
+    #[test]
+    fn preserve_slide_leaves_an_up_to_date_copy_alone() {
+        let dir = ScratchDir::create().unwrap();
+        let src = dir.0.join("frame_0001.png");
+        write_png(&src, 64, 48);
+        let dest = dir.0.join("slide_0001.png");
+        preserve_slide(&src, &dest, None).unwrap();
+        let old = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        std::fs::File::options()
+            .write(true)
+            .open(&dest)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        preserve_slide(&src, &dest, None).unwrap();
+        assert_eq!(std::fs::metadata(&dest).unwrap().modified().unwrap(), old);
+        assert_eq!(std::fs::read(&dest).unwrap(), std::fs::read(&src).unwrap());
+
+        // Different bytes are rewritten
+        write_png(&src, 32, 24);
+        preserve_slide(&src, &dest, None).unwrap();
+        assert_ne!(std::fs::metadata(&dest).unwrap().modified().unwrap(), old);
+    }
+
+    #[test]
+    fn preserve_slide_downscales_only_wide_slides() {
+        let dir = ScratchDir::create().unwrap();
+        let (wide, narrow) = (dir.0.join("wide.png"), dir.0.join("narrow.png"));
+        write_png(&wide, 3000, 30);
+        write_png(&narrow, 800, 30);
+
+        let dest = dir.0.join("slide_0001.png");
+        preserve_slide(&wide, &dest, Some(1280)).unwrap();
+        assert_eq!(image::image_dimensions(&dest).unwrap(), (1280, 12));
+
+        let dest = dir.0.join("slide_0002.png");
+        preserve_slide(&narrow, &dest, Some(1280)).unwrap();
+        assert_eq!(
+            std::fs::read(&dest).unwrap(),
+            std::fs::read(&narrow).unwrap()
+        );
+    }
+
+    /// A 64x64 frame of 8x8 blocks with pseudo-random shades: different seeds
+    /// hash far apart, the same seed identically.
+    fn noise_frame(seed: u32) -> image::GrayImage {
+        image::GrayImage::from_fn(64, 64, |x, y| {
+            let block = (y / 8) * 8 + x / 8;
+            let v = (block + 1)
+                .wrapping_mul(2_654_435_761)
+                .wrapping_add(seed.wrapping_mul(40_503))
+                .rotate_left(seed % 31 + 1);
+            image::Luma([40 + (v >> 24) as u8 % 200])
+        })
+    }
+
+    /// Frames `frame_0001.png`... in `dir`, one per seed.
+    fn write_frames(dir: &Path, seeds: &[u32]) -> Vec<PathBuf> {
+        seeds
+            .iter()
+            .enumerate()
+            .map(|(i, &seed)| {
+                let path = dir.join(format!("frame_{:04}.png", i + 1));
+                noise_frame(seed).save(&path).unwrap();
+                path
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parallel_hashing_keeps_the_same_frames_as_sequential() {
+        let dir = ScratchDir::create().unwrap();
+        // Four slides, each shown for three frames
+        let seeds: Vec<u32> = (0..12).map(|i| i / 3).collect();
+        let paths = write_frames(&dir.0, &seeds);
+        let dedup_on = |threads: usize| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            pool.install(|| dedup_frames(&paths, 0.9, None, &AverageHasher, false))
+        };
+        let (sequential, parallel) = (dedup_on(1), dedup_on(4));
+        assert_eq!(sequential.unique, [0, 3, 6, 9].map(|i| paths[i].clone()));
+        assert_eq!(parallel.unique, sequential.unique);
+        assert_eq!(parallel.hashes, sequential.hashes);
+    }
+
+    #[test]
+    fn dedup_hashes_skips_unhashable_frames() {
+        let paths: Vec<PathBuf> = (0..6)
+            .map(|i| PathBuf::from(format!("f{}.jpg", i)))
+            .collect();
+        let hex = |h: u64| Some(format!("{:016x}", h));
+        let hashes = [None, hex(0), None, hex(0b1), hex(u64::MAX), None];
+        let result = dedup_hashes(&paths, &hashes, None, 0.9, &AverageHasher);
+        assert_eq!(result.unique, [paths[1].clone(), paths[4].clone()]);
+        let decisions: Vec<(&Path, &Path, bool)> = result
+            .comparisons
+            .iter()
+            .map(|c| (c.frame.as_path(), c.closest.as_path(), c.merged))
+            .collect();
+        assert_eq!(
+            decisions,
+            [
+                (paths[3].as_path(), paths[1].as_path(), true),
+                (paths[4].as_path(), paths[1].as_path(), false),
+            ]
+        );
+        assert_eq!(result.hashes, hashes);
+        assert_eq!(result.stats().frames_examined, 3);
+
+        let none = dedup_hashes(&paths[..2], &[None, None], None, 0.9, &AverageHasher);
+        assert!(none.unique.is_empty() && none.comparisons.is_empty());
+    }
+
+    #[test]
+    fn threshold_advice_flags_too_strict_and_too_loose() {
+        let strict = threshold_advice(100, 90, 600.0, 0.9).unwrap();
+        assert!(strict.contains("too strict, try 0.85"), "{}", strict);
+        let loose = threshold_advice(600, 2, 3600.0, 0.9).unwrap();
+        assert!(
+            loose.contains("60 min") && loose.contains("too loose, try 0.93"),
+            "{}",
+            loose
+        );
+        // A plausible result, a short video, or too few frames to judge
+        assert_eq!(threshold_advice(100, 30, 1800.0, 0.9), None);
+        assert_eq!(threshold_advice(100, 1, 300.0, 0.9), None);
+        assert_eq!(threshold_advice(10, 10, 3600.0, 0.9), None);
+    }
+
+    #[test]
+    fn slideless_hint_tells_talking_heads_from_decks() {
+        let dir = ScratchDir::create().unwrap();
+        let n = SLIDELESS_SAMPLES as u32 * 3;
+        let subdir = |name: &str| {
+            let d = dir.0.join(name);
+            std::fs::create_dir(&d).unwrap();
+            d
+        };
+        // Every frame different vs. each slide held for six frames
+        let talk = write_frames(&subdir("talk"), &(0..n).collect::<Vec<_>>());
+        let deck = write_frames(&subdir("deck"), &(0..n).map(|i| i / 6).collect::<Vec<_>>());
+        assert!(slideless_hint(&talk, None, &AverageHasher).is_some());
+        assert_eq!(slideless_hint(&deck, None, &AverageHasher), None);
+        // Too few frames to judge
+        assert_eq!(slideless_hint(&talk[..10], None, &AverageHasher), None);
+    }
+
+    #[test]
+    fn slideless_hint_only_looks_at_the_slide_region() {
+        let dir = ScratchDir::create().unwrap();
+        // A static slide on the left, a moving speaker on the right
+        let slide = noise_frame(1000);
+        let paths: Vec<PathBuf> = (0..SLIDELESS_SAMPLES as u32 * 3)
+            .map(|i| {
+                let speaker = noise_frame(i);
+                let path = dir.0.join(format!("frame_{:04}.png", i + 1));
+                image::GrayImage::from_fn(128, 64, |x, y| {
+                    if x < 64 {
+                        *slide.get_pixel(x, y)
+                    } else {
+                        *speaker.get_pixel(x - 64, y)
+                    }
+                })
+                .save(&path)
+                .unwrap();
+                path
+            })
+            .collect();
+        let left_half = CropRegion {
+            x_pct: 0.0,
+            y_pct: 0.0,
+            w_pct: 50.0,
+            h_pct: 100.0,
+        };
+        assert!(slideless_hint(&paths, None, &AverageHasher).is_some());
+        assert_eq!(
+            slideless_hint(&paths, Some(left_half), &AverageHasher),
+            None
+        );
+    }
+
+    #[test]
+    fn pick_sharpest_prefers_the_in_focus_frame() {
+        let dir = ScratchDir::create().unwrap();
+        let slide = noise_frame(7);
+        let paths: Vec<PathBuf> = ["blurred.png", "sharp.png", "other.png"]
+            .iter()
+            .map(|n| dir.0.join(n))
+            .collect();
+        imageops::blur(&slide, 1.5).save(&paths[0]).unwrap();
+        slide.save(&paths[1]).unwrap();
+        noise_frame(8).save(&paths[2]).unwrap();
+        assert!(sharpness(&paths[1]).unwrap() > sharpness(&paths[0]).unwrap());
+
+        let dedup = dedup_frames(&paths, 0.9, None, &AverageHasher, false);
+        assert_eq!(dedup.unique, [paths[0].clone(), paths[2].clone()]);
+        assert_eq!(
+            sharpest_representatives(&dedup),
+            [paths[1].clone(), paths[2].clone()]
+        );
+    }
+
+    #[test]
+    fn color_aware_dedup_keeps_recolored_slides_apart() {
+        let dir = ScratchDir::create().unwrap();
+        let layout = noise_frame(3);
+        let two_tone = |light: [u8; 3], dark: [u8; 3]| {
+            image::RgbImage::from_fn(64, 64, |x, y| {
+                image::Rgb(if layout.get_pixel(x, y)[0] > 140 {
+                    light
+                } else {
+                    dark
+                })
+            })
+        };
+        let paths = [dir.0.join("light.png"), dir.0.join("dark.png")];
+        // Same layout, light theme vs. a yellow-on-navy theme
+        two_tone([250, 250, 250], [30, 30, 30])
+            .save(&paths[0])
+            .unwrap();
+        two_tone([240, 220, 20], [10, 10, 90])
+            .save(&paths[1])
+            .unwrap();
+
+        let plain = dedup_frames(&paths, 0.9, None, &AverageHasher, false);
+        assert_eq!(plain.hashes[0], plain.hashes[1]);
+        assert_eq!(plain.unique.len(), 1);
+        let colored = dedup_frames(&paths, 0.9, None, &AverageHasher, true);
+        assert_eq!(colored.unique, paths);
+
+        let hists = color_histograms(&paths, None);
+        let (a, b) = (hists[0].unwrap(), hists[1].unwrap());
+        assert!((histogram_similarity(&a, &a) - 1.0).abs() < 1e-6);
+        assert!(histogram_similarity(&a, &b) < COLOR_SIMILARITY);
+    }
+
+    #[test]
+    fn render_report_to_memory_matches_the_file() {
+        let dir = ScratchDir::create().unwrap();
+        let mut s = slide(1, "Ownership and borrowing in Rust");
+        s.image_path = dir.0.join("slide_0001.png");
+        write_png(&s.image_path, 16, 12);
+        let slides = [s];
+        let video = VideoInfo::default();
+        let mut m = meta("Talk", "https://youtu.be/x", &video);
+        m.embed_images = true;
+        for format in [ReportFormat::Md, ReportFormat::Html] {
+            let (written, failed) =
+                write_reports(&[format], "report", &m, "words", &slides, &dir.0);
+            assert!(failed.is_empty(), "{:?}", failed);
+            let mut buf = Vec::new();
+            render_report(format, &m, "words", &slides, &mut buf).unwrap();
+            assert_eq!(buf, std::fs::read(&written[0]).unwrap(), "{:?}", format);
+            assert!(String::from_utf8(buf)
+                .unwrap()
+                .contains("data:image/png;base64,"));
+        }
+    }
+
+    #[test]
+    fn write_atomic_keeps_the_original_when_writing_fails() {
+        let dir = ScratchDir::create().unwrap();
+        let path = dir.0.join("report.md");
+        std::fs::write(&path, "old report").unwrap();
+        let result = write_atomic(&path, |out| {
+            out.write_all(b"half a new rep")?;
+            Err("render failed".into())
+        });
+        assert_eq!(result.unwrap_err().to_string(), "render failed");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "old report");
+        let left: Vec<_> = std::fs::read_dir(&dir.0)
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name())
+            .collect();
+        assert_eq!(left, ["report.md"]);
+
+        write_atomic(&path, |out| Ok(out.write_all(b"new report")?)).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new report");
+        assert!(!dir.0.join("report.md.tmp").exists());
+    }
+
+    #[test]
+    fn output_lock_is_exclusive_until_dropped() {
+        let dir = ScratchDir::create().unwrap();
+        let first = lock_output_dir(&dir.0).unwrap();
+        let err = lock_output_dir(&dir.0).unwrap_err().to_string();
+        assert!(
+            err.contains("another yt-sl run is already writing"),
+            "{}",
+            err
+        );
+        drop(first);
+        lock_output_dir(&dir.0).unwrap();
+    }
+
+    #[test]
+    fn spanish_reports_translate_the_headings() {
+        let video = VideoInfo {
+            uploader: Some("ferris".to_string()),
+            view_count: Some(1500),
+            ..VideoInfo::default()
+        };
+        let settings = settings_for(&["--report-language", "es"]);
+        let mut m = meta("Charla", "https://youtu.be/x", &video);
+        m.labels = ReportLanguage::Es.labels();
+        m.run_settings = &settings;
+        let mut s = slide(1, "Propiedad y préstamo en Rust");
+        s.transcript = "hoy hablamos de propiedad".to_string();
+
+        let mut md = Vec::new();
+        render_report(ReportFormat::Md, &m, "texto", &[s], &mut md).unwrap();
+        let md = String::from_utf8(md).unwrap();
+        for heading in [
+            "**Fuente:** [https://youtu.be/x]",
+            "**Autor:** ferris · **Visualizaciones:** 1,500",
+            "## Transcripción completa",
+            "<summary>Haz clic para expandir</summary>",
+            "## Diapositivas",
+            "### Diapositiva 1 (0:05)",
+            "#### Texto",
+            "#### Notas del orador",
+            "<summary>Ajustes (1 distintos de los predeterminados)</summary>",
+            "| Ajuste | Valor | Predeterminado |",
+        ] {
+            assert!(md.contains(heading), "missing {:?} in\n{}", heading, md);
+        }
+
+        let mut html = Vec::new();
+        render_report(ReportFormat::Html, &m, "", &[], &mut html).unwrap();
+        let html = String::from_utf8(html).unwrap();
+        assert!(html.contains("<h2>Diapositivas</h2>"));
+        assert!(html.contains("No se detectaron diapositivas en este vídeo."));
+        assert!(html.contains("Qué probar"));
+    }
+
+    #[test]
+    fn tokenize_lowercases_and_splits_on_punctuation() {
+        assert_eq!(
+            tokenize("Hello, World! async/await v2"),
+            ["hello", "world", "async", "await", "v2"]
+        );
+        assert!(tokenize(" -- ").is_empty());
+    }
+
+    #[test]
+    fn build_index_maps_tokens_to_reports_and_slides() {
+        let dir = ScratchDir::create().unwrap();
+        for (name, title, texts) in [
+            (
+                "a",
+                "Rust talk",
+                ["Ownership rules", "Borrowing: rules RULES"],
+            ),
+            ("b", "Go talk", ["Goroutines", "Channels and rules"]),
+        ] {
+            std::fs::create_dir(dir.0.join(name)).unwrap();
+            let slides = texts
+                .iter()
+                .enumerate()
+                .map(|(i, t)| slide(i + 1, t))
+                .collect();
+            let mut report = report_fixture(slides, vec![]);
+            report.title = title.to_string();
+            std::fs::write(
+                dir.0.join(name).join("report.json"),
+                serde_json::to_string(&report).unwrap(),
+            )
+            .unwrap();
+        }
+        build_index(&dir.0).unwrap();
+
+        let index: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.0.join("index.json")).unwrap())
+                .unwrap();
+        let refs = |token: &str| -> Vec<(String, u64)> {
+            index[token]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|e| {
+                    (
+                        e["video"].as_str().unwrap().to_string(),
+                        e["slide"].as_u64().unwrap(),
+                    )
+                })
+                .collect()
+        };
+        // One entry per slide, however often the token appears on it, in
+        // report path order
+        assert_eq!(
+            refs("rules"),
+            [
+                ("Rust talk".to_string(), 1),
+                ("Rust talk".to_string(), 2),
+                ("Go talk".to_string(), 2)
+            ]
+        );
+        assert_eq!(refs("goroutines"), [("Go talk".to_string(), 1)]);
+        assert_eq!(
+            index["ownership"][0]["report"],
+            dir.0.join("a/report.json").to_str().unwrap()
+        );
+        assert!(index.get("Ownership").is_none());
+    }
+
+    #[test]
+    fn similarity_csv_has_a_header_and_one_row_per_comparison() {
+        let dir = ScratchDir::create().unwrap();
+        let path = dir.0.join("similarity.csv");
+        let comparisons = [Comparison {
+            frame: PathBuf::from("frames/frame_0002.jpg"),
+            closest: PathBuf::from("frames/frame,0001.jpg"),
+            similarity: 0.953125,
+            merged: true,
+        }];
+        write_similarity_csv(&path, &comparisons).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "frame_a,frame_b,similarity,merged\n\"frame,0001.jpg\",frame_0002.jpg,0.9531,true\n"
+        );
+    }
+
+    #[test]
+    fn verify_slide_images_names_every_bad_file() {
+        let dir = ScratchDir::create().unwrap();
+        let mut slides = [slide(1, ""), slide(2, ""), slide(3, "")];
+        for s in &mut slides {
+            s.image_path = dir.0.join(format!("slide_{:04}.png", s.index));
+        }
+        write_png(&slides[0].image_path, 8, 8);
+        std::fs::write(&slides[2].image_path, b"").unwrap();
+        let err = verify_slide_images(&slides).unwrap_err().to_string();
+        assert!(!err.contains("slide_0001.png"), "{}", err);
+        assert!(
+            err.contains("slide_0002.png") && err.contains("slide_0003.png"),
+            "{}",
+            err
+        );
+        assert!(verify_slide_images(&slides[..1]).is_ok());
+    }
+
+    #[test]
+    fn human_bytes_switches_units_at_1024() {
+        assert_eq!(human_bytes(0), "0 B");
+        assert_eq!(human_bytes(1023), "1023 B");
+        assert_eq!(human_bytes(1024), "1.0 KiB");
+        assert_eq!(human_bytes(1536), "1.5 KiB");
+        assert_eq!(human_bytes(1024 * 1024), "1.0 MiB");
+        assert_eq!(human_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+
+    #[test]
+    fn dir_size_sums_files_in_nested_directories() {
+        let dir = ScratchDir::create().unwrap();
+        std::fs::create_dir(dir.0.join("slides")).unwrap();
+        std::fs::write(dir.0.join("report.md"), vec![b'x'; 100]).unwrap();
+        std::fs::write(dir.0.join("slides/slide_0001.png"), vec![0u8; 2000]).unwrap();
+        assert_eq!(dir_size(&dir.0), 2100);
+        assert_eq!(dir_size(&dir.0.join("missing")), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn spawn_limited_never_exceeds_the_concurrency_bound() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let sem = std::sync::Arc::new(Semaphore::new(3));
+        let in_flight = std::sync::Arc::new(AtomicUsize::new(0));
+        let peak = std::sync::Arc::new(AtomicUsize::new(0));
+        let handles: Vec<_> = (0..20)
+            .map(|i| {
+                let (in_flight, peak) = (in_flight.clone(), peak.clone());
+                spawn_limited(&sem, async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(std::time::Duration::from_millis(5));
+                    tokio::task::yield_now().await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    i
+                })
+            })
+            .collect();
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert_eq!(handle.await.unwrap(), i);
+        }
+        let peak = peak.load(Ordering::SeqCst);
+        assert!((1..=3).contains(&peak), "peak in flight: {}", peak);
+    }
+
+    #[tokio::test]
+    async fn output_pointing_at_a_regular_file_is_rejected() {
+        let dir = ScratchDir::create().unwrap();
+        let frames = dir.0.join("frames");
+        std::fs::create_dir(&frames).unwrap();
+        write_frames(&frames, &[1]);
+        let output = dir.0.join("slides");
+        std::fs::write(&output, "not a dir").unwrap();
+        let args = Args::try_parse_from([
+            "yt-sl",
+            "--frames",
+            frames.to_str().unwrap(),
+            "--output",
+            output.to_str().unwrap(),
+        ])
+        .unwrap();
+        let err = run(args).await.unwrap_err().to_string();
+        assert_eq!(
+            err,
+            format!(
+                "--output {} exists but is not a directory; pass a directory path",
+                output.display()
+            )
+        );
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "not a dir");
+    }
+
+    #[test]
+    fn settings_line_is_one_line_with_defaults_for_changed_settings() {
+        let line = settings_line(&settings_for(&["--threshold", "0.85", "--max-slides", "3"]));
+        assert!(!line.contains('\n'), "{}", line);
+        assert!(line.contains("--threshold=0.85 (default 0.90)"), "{}", line);
+        assert!(line.contains("--max-slides=3"), "{}", line);
+        assert!(line.contains(", --concurrency=4"), "{}", line);
+        assert!(!line.contains("--frames"), "{}", line);
+    }
+}
@@ -4,33 +4,290 @@
 # Usage:
 #   ./yt-sl.sh https://youtu.be/g0047beVND4
 #   ./yt-sl.sh https://youtu.be/g0047beVND4 --interval 3
+#   ./yt-sl.sh https://youtu.be/g0047beVND4 --interval-frames 90
+#   ./yt-sl.sh https://youtu.be/g0047beVND4 --frame-budget 300
 #   ./yt-sl.sh https://youtu.be/g0047beVND4 --title "My Talk"
+#   ./yt-sl.sh https://youtu.be/g0047beVND4 --frame-format png
+#   ./yt-sl.sh https://youtu.be/g0047beVND4 --preset code
+#   ./yt-sl.sh https://youtu.be/g0047beVND4 --sample 120
+#   ./yt-sl.sh "https://youtu.be/g0047beVND4?t=10m" --from 1h2m3s
+#   ./yt-sl.sh https://youtu.be/g0047beVND4 --subtitles --sub-langs "de.*"
+#   ./yt-sl.sh https://youtu.be/g0047beVND4 --download-retries 5 --stall-timeout 300
+#   ./yt-sl.sh https://youtu.be/g0047beVND4 --extract-timeout 1800
+#   ./yt-sl.sh https://youtu.be/g0047beVND4 --probe-only
+#   ./yt-sl.sh https://youtu.be/g0047beVND4 --strict-url
+#   ./yt-sl.sh https://youtu.be/g0047beVND4 --ytdlp-arg --limit-rate --ytdlp-arg 2M
 
 set -euo pipefail
 
 URL="${1:?Usage: yt-sl.sh <youtube-url> [--interval N] [--title TITLE] [extra yt-sl flags...]}"
 shift
 
-CACHE="${HOME}/Library/Application Support/yt-sl/cache"
+# Links pasted URL-encoded (https%3A%2F%2F...): decode before anything else
+if [[ "$URL" != *://* && "$URL" =~ ^https?%3[aA]%2[fF]%2[fF] ]]; then
+  URL=$(printf '%b' "${URL//%/\\x}")
+fi
+
+# Share links carry tracking params (si=, pp=, feature=, utm_*) that only
+# clutter the report's source link; drop them from YouTube URLs
+if [[ "$URL" =~ ^https?://((www|m|music)\.)?(youtube\.com|youtu\.be)/ && "$URL" == *\?* ]]; then
+  URL_BASE="${URL%%\?*}"
+  URL_QUERY="${URL#*\?}"
+  URL_FRAGMENT=""
+  if [[ "$URL_QUERY" == *#* ]]; then
+    URL_FRAGMENT="#${URL_QUERY#*#}"
+    URL_QUERY="${URL_QUERY%%#*}"
+  fi
+  KEPT=""
+  IFS='&' read -r -a URL_PARAMS <<<"$URL_QUERY"
+  for param in ${URL_PARAMS[@]+"${URL_PARAMS[@]}"}; do
+    case "${param%%=*}" in
+      si|pp|feature|utm_*|"") ;;
+      *) KEPT="${KEPT:+$KEPT&}$param" ;;
+    esac
+  done
+  URL="$URL_BASE${KEPT:+?$KEPT}$URL_FRAGMENT"
+fi
+
+# Start offset in seconds from 600 / 600s / 10m / 10m0s / 1m30 / 1h2m3s; empty if invalid
+parse_start_time() {
+  if [[ "$1" =~ ^([0-9]+)s?$ ]]; then
+    echo "$((10#${BASH_REMATCH[1]}))"
+  elif [[ "$1" =~ ^(([0-9]+)h)?([0-9]+)m([0-9]+)$ ]]; then
+    echo "$((10#${BASH_REMATCH[2]:-0} * 3600 + 10#${BASH_REMATCH[3]} * 60 + 10#${BASH_REMATCH[4]}))"
+  elif [[ "$1" =~ ^(([0-9]+)h)?(([0-9]+)m)?(([0-9]+)s)?$ && -n "$1" ]]; then
+    echo "$((10#${BASH_REMATCH[2]:-0} * 3600 + 10#${BASH_REMATCH[4]:-0} * 60 + 10#${BASH_REMATCH[6]:-0}))"
+  fi
+}
+
+# A shared link's t= / start= is where the viewer meant to start watching;
+# extraction starts there unless --from says otherwise
+URL_START=""
+if [[ "$URL" =~ [?\&](t|start)=([^\&#]*) ]]; then
+  URL_START=$(parse_start_time "${BASH_REMATCH[2]}")
+  if [[ -z "$URL_START" ]]; then
+    echo "  warning: ignoring unrecognized start time ${BASH_REMATCH[1]}=${BASH_REMATCH[2]} in the URL" >&2
+  fi
+fi
+
+# Platform cache dir: macOS Application Support, XDG cache elsewhere
+if [[ -n "${YT_SL_CACHE:-}" ]]; then
+  CACHE="$YT_SL_CACHE"
+elif [[ "$(uname -s)" == "Darwin" ]]; then
+  CACHE="${HOME}/Library/Application Support/yt-sl/cache"
+else
+  CACHE="${XDG_CACHE_HOME:-$HOME/.cache}/yt-sl"
+fi
 OUTPUT="./output"
-INTERVAL=5
+INTERVAL=""
+INTERVAL_FRAMES=""
+FRAME_BUDGET=""
 TITLE="Untitled"
+FRAME_FORMAT=""
+DECIMATE=""
+THRESHOLD=""
+PRESET=""
+SAVE_VIDEO=0
+SAMPLE=""
+FROM=""
+SUBTITLES=0
+SUB_LANGS="en.*"
+DOWNLOAD_RETRIES=3
+STALL_TIMEOUT=""
+EXTRACT_TIMEOUT=""
+PROBE_ONLY=0
+STRICT_URL=0
+YTDLP_ARGS=()
+FFMPEG_ARGS=()
 EXTRA_ARGS=()
 
 # Parse optional args
 while [[ $# -gt 0 ]]; do
   case "$1" in
     --interval|-i) INTERVAL="$2"; shift 2 ;;
+    --interval-frames) INTERVAL_FRAMES="$2"; shift 2 ;;
+    --adaptive-interval) FRAME_BUDGET="${FRAME_BUDGET:-500}"; shift ;;
+    --frame-budget) FRAME_BUDGET="$2"; shift 2 ;;
     --title) TITLE="$2"; shift 2 ;;
     --output|-o) OUTPUT="$2"; shift 2 ;;
+    --frame-format) FRAME_FORMAT="$2"; shift 2 ;;
+    --decimate) DECIMATE=1; shift ;;
+    --no-decimate) DECIMATE=0; shift ;;
+    --threshold|-T) THRESHOLD="$2"; shift 2 ;;
+    --preset) PRESET="$2"; shift 2 ;;
+    --save-video) SAVE_VIDEO=1; shift ;;
+    --sample) SAMPLE="$2"; shift 2 ;;
+    --from) FROM="$2"; shift 2 ;;
+    --subtitles) SUBTITLES=1; shift ;;
+    --sub-langs) SUB_LANGS="$2"; shift 2 ;;
+    --download-retries) DOWNLOAD_RETRIES="$2"; shift 2 ;;
+    --stall-timeout) STALL_TIMEOUT="$2"; shift 2 ;;
+    --extract-timeout) EXTRACT_TIMEOUT="$2"; shift 2 ;;
+    --probe-only) PROBE_ONLY=1; shift ;;
+    --strict-url) STRICT_URL=1; shift ;;
+    --ytdlp-arg) YTDLP_ARGS+=("$2"); shift 2 ;;
+    --ffmpeg-arg) FFMPEG_ARGS+=("$2"); shift 2 ;;
     *) EXTRA_ARGS+=("$1"); shift ;;
   esac
 done
 
-# Get video ID and title
-VIDEO_INFO=$(yt-dlp --print id --print title "$URL" 2>/dev/null || echo "")
+# Legacy http:// YouTube links: upgrade to https so the report links the canonical
+# URL, unless --strict-url asks for anything but https to be rejected
+if [[ "$STRICT_URL" == 1 ]]; then
+  if [[ "$URL" != https://* ]]; then
+    echo "error: --strict-url only accepts https:// URLs (got $URL)" >&2
+    exit 1
+  fi
+elif [[ "$URL" =~ ^http://((www|m|music)\.)?(youtube\.com|youtu\.be)/ ]]; then
+  URL="https://${URL#http://}"
+fi
+
+if [[ -n "$INTERVAL_FRAMES" && -n "$INTERVAL" ]]; then
+  echo "error: --interval and --interval-frames are mutually exclusive" >&2
+  exit 1
+fi
+if [[ -n "$FRAME_BUDGET" && ( -n "$INTERVAL" || -n "$INTERVAL_FRAMES" ) ]]; then
+  echo "error: --adaptive-interval/--frame-budget can't be combined with --interval or --interval-frames" >&2
+  exit 1
+fi
+if [[ -n "$FRAME_BUDGET" && ! "$FRAME_BUDGET" =~ ^[1-9][0-9]*$ ]]; then
+  echo "error: --frame-budget must be a positive integer (got $FRAME_BUDGET)" >&2
+  exit 1
+fi
+if [[ -n "$INTERVAL_FRAMES" && ! "$INTERVAL_FRAMES" =~ ^[1-9][0-9]*$ ]]; then
+  echo "error: --interval-frames must be a positive integer (got $INTERVAL_FRAMES)" >&2
+  exit 1
+fi
+
+if [[ ! "$DOWNLOAD_RETRIES" =~ ^[1-9][0-9]*$ ]]; then
+  echo "error: --download-retries must be a positive integer (got $DOWNLOAD_RETRIES)" >&2
+  exit 1
+fi
+
+if [[ -n "$STALL_TIMEOUT" && ! "$STALL_TIMEOUT" =~ ^[1-9][0-9]*$ ]]; then
+  echo "error: --stall-timeout must be a positive number of seconds (got $STALL_TIMEOUT)" >&2
+  exit 1
+fi
+
+if [[ -n "$EXTRACT_TIMEOUT" && ! "$EXTRACT_TIMEOUT" =~ ^[1-9][0-9]*$ ]]; then
+  echo "error: --extract-timeout must be a positive number of seconds (got $EXTRACT_TIMEOUT)" >&2
+  exit 1
+fi
+
+if [[ -n "$SAMPLE" && ! "$SAMPLE" =~ ^[1-9][0-9]*$ ]]; then
+  echo "error: --sample must be a positive number of seconds (got $SAMPLE)" >&2
+  exit 1
+fi
+
+if [[ -n "$FROM" ]]; then
+  FROM_SECS=$(parse_start_time "$FROM")
+  if [[ -z "$FROM_SECS" ]]; then
+    echo "error: --from must be seconds or a 1h2m3s-style time (got $FROM)" >&2
+    exit 1
+  fi
+  FROM="$FROM_SECS"
+elif [[ -n "$URL_START" && "$URL_START" -gt 0 ]]; then
+  FROM="$URL_START"
+  echo "  starting at ${FROM}s (from the URL's start time; pass --from 0 to start at the beginning)" >&2
+fi
+if [[ "$FROM" == "0" ]]; then FROM=""; fi
+
+# Presets fill in whatever wasn't given explicitly (flags always win):
+#   lecture     interval 5s,  jpg, threshold 0.90, no decimate (the defaults)
+#   code        interval 3s,  png, threshold 0.95, no decimate (small text edits matter)
+#   whiteboard  interval 10s, png, threshold 0.85, decimate    (slow incremental drawing)
+#   fast        interval 10s, jpg, threshold 0.90, decimate    (fewest frames to process)
+case "$PRESET" in
+  ""|lecture) P_INTERVAL=5;  P_FORMAT=jpg; P_THRESHOLD=0.90; P_DECIMATE=0 ;;
+  code)       P_INTERVAL=3;  P_FORMAT=png; P_THRESHOLD=0.95; P_DECIMATE=0 ;;
+  whiteboard) P_INTERVAL=10; P_FORMAT=png; P_THRESHOLD=0.85; P_DECIMATE=1 ;;
+  fast)       P_INTERVAL=10; P_FORMAT=jpg; P_THRESHOLD=0.90; P_DECIMATE=1 ;;
+  *) echo "error: --preset must be lecture, code, whiteboard or fast (got $PRESET)" >&2; exit 1 ;;
+esac
+if [[ -z "$INTERVAL" && -z "$INTERVAL_FRAMES" && -z "$FRAME_BUDGET" ]]; then INTERVAL="$P_INTERVAL"; fi
+FRAME_FORMAT="${FRAME_FORMAT:-$P_FORMAT}"
+THRESHOLD="${THRESHOLD:-$P_THRESHOLD}"
+DECIMATE="${DECIMATE:-$P_DECIMATE}"
+
+# Frame image format: jpg (small) or png (lossless, crisper text for OCR)
+case "$FRAME_FORMAT" in
+  jpg|jpeg) FRAME_FORMAT="jpg"; QUALITY_ARGS=(-q:v 2) ;;
+  png) QUALITY_ARGS=() ;;
+  *) echo "error: --frame-format must be jpg or png (got $FRAME_FORMAT)" >&2; exit 1 ;;
+esac
+
+# Get video ID and title. Slow responses are retried with a longer socket
+# timeout and backoff; private/removed videos fail straight through.
+INFO_ERR=$(mktemp)
+trap 'rm -f "$INFO_ERR"' EXIT
+VIDEO_INFO=""
+# --probe-only: one extra line with the metadata as a JSON object
+PROBE_PRINT=()
+if [[ $PROBE_ONLY -eq 1 ]]; then
+  PROBE_PRINT=(--print "%(.{id,title,uploader,upload_date,view_count,duration,width,height,fps,chapters})j")
+fi
+for attempt in 1 2 3; do
+  if VIDEO_INFO=$(yt-dlp ${YTDLP_ARGS[@]+"${YTDLP_ARGS[@]}"} --socket-timeout $((10 * attempt)) --print id --print title \
+    --print "%(filesize,filesize_approx|0)s %(duration|0)s" \
+    --print "%(upload_date|-)s %(view_count|-)s" --print "%(uploader|)s" \
+    ${PROBE_PRINT[@]+"${PROBE_PRINT[@]}"} "$URL" 2>"$INFO_ERR"); then
+    break
+  fi
+  VIDEO_INFO=""
+  if grep -qiE 'private video|video unavailable|not available' "$INFO_ERR"; then
+    break
+  fi
+  if [[ $attempt -lt 3 ]]; then
+    echo "  metadata lookup failed (attempt $attempt/3), retrying in $((2 ** attempt))s..." >&2
+    sleep $((2 ** attempt))
+  fi
+done
+
+# Metadata only: print it and stop before anything is downloaded or extracted
+if [[ $PROBE_ONLY -eq 1 ]]; then
+  if [[ -z "$VIDEO_INFO" ]]; then
+    echo "error: could not fetch metadata for $URL:" >&2
+    grep -i error "$INFO_ERR" | tail -3 | sed 's/^/  /' >&2
+    exit 1
+  fi
+  echo "$VIDEO_INFO" | sed -n 6p
+  exit 0
+fi
+
 VIDEO_ID=$(echo "$VIDEO_INFO" | head -1)
-VIDEO_TITLE=$(echo "$VIDEO_INFO" | tail -1)
+VIDEO_TITLE=$(echo "$VIDEO_INFO" | sed -n 2p)
+read -r VIDEO_BYTES VIDEO_DURATION _ <<<"$(echo "$VIDEO_INFO" | sed -n 3p) 0 0"
+read -r UPLOAD_DATE VIEW_COUNT _ <<<"$(echo "$VIDEO_INFO" | sed -n 4p)"
+UPLOADER=$(echo "$VIDEO_INFO" | sed -n 5p)
+# Report header details, passed only when yt-dlp knew them
+META_ARGS=()
+if [[ -n "$UPLOADER" ]]; then META_ARGS+=(--uploader "$UPLOADER"); fi
+if [[ "${UPLOAD_DATE:-}" =~ ^[0-9]{8}$ ]]; then META_ARGS+=(--upload-date "$UPLOAD_DATE"); fi
+if [[ "${VIEW_COUNT:-}" =~ ^[0-9]+$ ]]; then META_ARGS+=(--view-count "$VIEW_COUNT"); fi
+
+if [[ -n "$FROM" && "${VIDEO_DURATION%.*}" -gt 0 && "$FROM" -ge "${VIDEO_DURATION%.*}" ]]; then
+  echo "error: start time ${FROM}s is past the end of the video (${VIDEO_DURATION%.*}s)" >&2
+  exit 1
+fi
+
+# Adaptive interval: spread the frame budget over the (sampled) duration,
+# clamped to 0.1-60s. Falls back to the preset interval if the length is unknown.
+if [[ -n "$FRAME_BUDGET" ]]; then
+  read -r INTERVAL EST_FRAMES _ <<<"$(awk -v dur="$VIDEO_DURATION" -v from="$FROM" -v sample="$SAMPLE" -v budget="$FRAME_BUDGET" 'BEGIN {
+      if (dur > 0) dur -= from
+      if (sample > 0 && (dur <= 0 || sample < dur)) dur = sample
+      if (dur <= 0) exit
+      i = dur / budget
+      i = i < 0.1 ? 0.1 : (i > 60 ? 60 : i)
+      printf "%.1f %d", i, dur / i
+    }')"
+  if [[ -n "$INTERVAL" ]]; then
+    echo "  adaptive interval: ${INTERVAL}s (~$EST_FRAMES frames)"
+  else
+    INTERVAL="$P_INTERVAL"
+    echo "  warning: video length unknown, using the ${INTERVAL}s interval instead of --frame-budget" >&2
+  fi
+fi
 
 if [[ -z "$VIDEO_ID" ]]; then
   # Fallback: extract from URL
@@ -52,44 +309,244 @@ if [[ -z "$OUTPUT_NAME" ]]; then
   OUTPUT_NAME="$VIDEO_ID"
 fi
 
+# Sample runs: only the first N seconds, kept apart from the full run's
+# frames and report so tuning never clobbers them
+REPORT_TITLE="$TITLE"
+FRAMES_KEY="$VIDEO_ID"
+INPUT_ARGS=()
+if [[ -n "$SAMPLE" ]]; then
+  REPORT_TITLE="$TITLE (sample: first ${SAMPLE}s)"
+  OUTPUT_NAME="$OUTPUT_NAME-sample"
+  FRAMES_KEY="$VIDEO_ID-sample$SAMPLE"
+  INPUT_ARGS+=(-t "$SAMPLE")
+fi
+# Late start (--from or the URL's t=): seek before decoding, and keep these
+# frames and the report apart from a full run's like sample runs do
+if [[ -n "$FROM" ]]; then
+  REPORT_TITLE="$REPORT_TITLE (from ${FROM}s)"
+  OUTPUT_NAME="$OUTPUT_NAME-from${FROM}s"
+  FRAMES_KEY="$FRAMES_KEY-from$FROM"
+  INPUT_ARGS=(-ss "$FROM" ${INPUT_ARGS[@]+"${INPUT_ARGS[@]}"})
+fi
+
 echo "[1/5] Video: $TITLE ($VIDEO_ID)"
 
 VIDEOS_DIR="$CACHE/videos"
-FRAMES_DIR="$CACHE/frames/$VIDEO_ID"
+FRAMES_DIR="$CACHE/frames/$FRAMES_KEY"
 mkdir -p "$VIDEOS_DIR" "$FRAMES_DIR"
 
 # Download video (cached)
 VIDEO_PATH="$VIDEOS_DIR/$VIDEO_ID.mp4"
+
+# Disk-space pre-check: the video (unless cached) plus the frames (unless
+# already extracted), estimated from the metadata. Frame sizes are rough
+# per-format averages at 1024px wide; unknown sizes/durations count as zero.
+NEED_KB=$(awk -v bytes="$VIDEO_BYTES" -v dur="$VIDEO_DURATION" -v from="$FROM" -v sample="$SAMPLE" \
+  -v interval="${INTERVAL:-0}" -v frames="${INTERVAL_FRAMES:-0}" -v fmt="$FRAME_FORMAT" \
+  -v have_video="$([[ -f "$VIDEO_PATH" ]] && echo 1 || echo 0)" \
+  -v have_frames="$([[ -f "$FRAMES_DIR/params.txt" ]] && echo 1 || echo 0)" 'BEGIN {
+    dur = dur > from ? dur - from : 0
+    if (sample > 0 && sample < dur) dur = sample
+    step = interval > 0 ? interval : frames / 30
+    per_frame = fmt == "png" ? 1500 : 200
+    kb = have_video ? 0 : bytes / 1024
+    if (!have_frames && step > 0) kb += dur / step * per_frame
+    printf "%d", kb
+  }')
+FREE_KB=$(df -Pk "$CACHE" 2>/dev/null | awk 'NR == 2 { print $4 }')
+if [[ "$NEED_KB" -gt 0 && -n "$FREE_KB" && "$FREE_KB" -lt "$NEED_KB" ]]; then
+  echo "error: not enough disk space in $CACHE: need ~$((NEED_KB / 1024)) MB, $((FREE_KB / 1024)) MB free" >&2
+  echo "  free some space or point YT_SL_CACHE at a larger drive" >&2
+  exit 1
+fi
+
 if [[ -f "$VIDEO_PATH" ]]; then
   echo "[2/5] Video cached: $VIDEO_PATH"
 else
   echo "[2/5] Downloading video..."
-  yt-dlp -f "bestvideo[ext=mp4]+bestaudio[ext=m4a]/best[ext=mp4]/best" \
-    -o "$VIDEO_PATH" "$URL"
+  # Network hiccups are retried with backoff (yt-dlp resumes the .part file);
+  # unavailable videos and missing formats fail straight through
+  attempt=1
+  until yt-dlp ${YTDLP_ARGS[@]+"${YTDLP_ARGS[@]}"} -f "bestvideo[ext=mp4]+bestaudio[ext=m4a]/best[ext=mp4]/best" \
+    -o "$VIDEO_PATH" "$URL" 2>"$INFO_ERR"; do
+    if grep -qiE 'private video|video unavailable|not available|requested format' "$INFO_ERR" ||
+      [[ $attempt -ge $DOWNLOAD_RETRIES ]]; then
+      echo "error: download failed after $attempt attempt(s):" >&2
+      grep -i error "$INFO_ERR" | tail -3 | sed 's/^/  /' >&2
+      exit 1
+    fi
+    echo "  download failed (attempt $attempt/$DOWNLOAD_RETRIES), retrying in $((2 ** attempt))s..." >&2
+    sleep $((2 ** attempt))
+    attempt=$((attempt + 1))
+  done
+fi
+
+# Probe the downloaded file: container/codec/resolution go into the log and
+# source.txt (next to ffmpeg.log) for diagnostics, and the real frame rate
+# maps frame-count intervals back to seconds
+PROBE=$(ffprobe -v error -select_streams v:0 \
+  -show_entries stream=codec_name,width,height,r_frame_rate:format=format_name,duration \
+  -of default=noprint_wrappers=1 "$VIDEO_PATH" 2>/dev/null || true)
+probe_field() { echo "$PROBE" | sed -n "s/^$1=//p" | head -1; }
+FPS=$(probe_field r_frame_rate | awk -F/ '$1 > 0 { printf "%.3f", ($2 > 0 ? $1 / $2 : $1) }')
+FPS="${FPS:-25}"
+SOURCE_INFO="container=$(probe_field format_name) codec=$(probe_field codec_name)"
+SOURCE_INFO="$SOURCE_INFO resolution=$(probe_field width)x$(probe_field height) fps=$FPS"
+echo "  source: $SOURCE_INFO"
+echo "$SOURCE_INFO" > "$FRAMES_DIR/source.txt"
+# Vertical (Shorts-style) video: keep the long side at 1024px like landscape
+# frames get, rather than 1024px wide and nearly 2000px tall
+SCALE="1024:-1"
+SCALE_KEY="1024"
+if [[ "$(probe_field height)" =~ ^[0-9]+$ && "$(probe_field width)" =~ ^[0-9]+$ &&
+  "$(probe_field height)" -gt "$(probe_field width)" ]]; then
+  SCALE="-2:1024"
+  SCALE_KEY="$SCALE"
+  echo "  portrait video detected; vertical videos/Shorts rarely contain slides"
+fi
+# Seconds ffmpeg will decode, for extraction progress
+EXTRACT_SECS=$(awk -v d="$(probe_field duration)" -v from="$FROM" -v s="$SAMPLE" \
+  'BEGIN { d += 0; d = d > from ? d - from : 0; if (s > 0 && (d <= 0 || s < d)) d = s; printf "%d", d }')
+
+if [[ -n "$INTERVAL_FRAMES" ]]; then
+  SELECT_EVERY="$INTERVAL_FRAMES"
+  # yt-sl only uses --interval to estimate timestamps, so round to whole seconds
+  INTERVAL=$(awk -v n="$INTERVAL_FRAMES" -v fps="$FPS" 'BEGIN { s = int(n / fps + 0.5); print (s < 1 ? 1 : s) }')
+else
+  SELECT_EVERY=$(awk -v i="$INTERVAL" -v fps="$FPS" 'BEGIN { n = int(i * fps + 0.5); print (n < 1 ? 1 : n) }')
+  # Same rounding for fractional (e.g. adaptive) intervals
+  INTERVAL=$(awk -v i="$INTERVAL" 'BEGIN { s = int(i + 0.5); print (s < 1 ? 1 : s) }')
+fi
+
+FRAME_FILTER="select='gt(scene,0.2)+not(mod(n,$SELECT_EVERY))'"
+if [[ "$DECIMATE" -eq 1 ]]; then
+  # Drop selected frames that are near-identical to the previous one before
+  # they hit disk, so static stretches cost no decode/hash time in yt-sl
+  FRAME_FILTER="$FRAME_FILTER,mpdecimate"
 fi
 
-# Extract frames using scene detection (skip if already done)
-FRAME_COUNT=$(find "$FRAMES_DIR" -name "*.jpg" 2>/dev/null | wc -l | tr -d ' ')
-if [[ "$FRAME_COUNT" -gt 0 ]]; then
+# Percent of EXTRACT_SECS decoded so far, from ffmpeg's -progress key=value
+# file (out_time_us is the position reached). Empty until known.
+ffmpeg_progress() {
+  awk -F= -v dur="$EXTRACT_SECS" '$1 == "out_time_us" { t = $2 + 0 }
+    END { if (dur > 0 && t > 0) { p = t / 1e6 / dur * 100; printf "%d%%", (p > 100 ? 100 : p) } }' \
+    "$1" 2>/dev/null || true
+}
+
+# Report extraction progress every 10s, and watch for stalls: ffmpeg writes
+# stats to its log a few times a second, so a log that stops growing means a
+# hung process. Warn after a minute of silence; with --stall-timeout, kill it
+# once that many seconds pass. --extract-timeout caps the whole extraction,
+# for an ffmpeg that keeps logging but never finishes (e.g. a bad seek).
+watch_ffmpeg() {
+  local pid="$1" log="$2" progress_file="$3" size last="" idle=0 warned=0 ticks=0 pct
+  while kill -0 "$pid" 2>/dev/null; do
+    sleep 1
+    ticks=$((ticks + 1))
+    if [[ -n "$EXTRACT_TIMEOUT" && $ticks -ge $EXTRACT_TIMEOUT ]]; then
+      echo "error: frame extraction still running after ${ticks}s, stopping ffmpeg (--extract-timeout)" >&2
+      kill "$pid" 2>/dev/null || true
+      return
+    fi
+    if [[ $((ticks % 10)) -eq 0 ]]; then
+      pct=$(ffmpeg_progress "$progress_file")
+      if [[ -n "$pct" ]]; then echo "  extracting: $pct"; fi
+    fi
+    size=$(wc -c <"$log" 2>/dev/null | tr -d ' ')
+    if [[ "$size" != "$last" ]]; then
+      last="$size"; idle=0; warned=0
+      continue
+    fi
+    idle=$((idle + 1))
+    if [[ -n "$STALL_TIMEOUT" && $idle -ge $STALL_TIMEOUT ]]; then
+      echo "error: no progress from ffmpeg for ${idle}s, stopping it (--stall-timeout)" >&2
+      kill "$pid" 2>/dev/null || true
+      return
+    fi
+    if [[ $idle -ge 60 && $warned -eq 0 ]]; then
+      echo "  warning: no progress from ffmpeg for ${idle}s, it may be stalled" >&2
+      warned=1
+    fi
+  done
+}
+
+# Extract frames using scene detection (skip if already done with the same
+# settings). params.txt is only written after a complete extraction, so an
+# interrupted run or a settings change triggers a clean re-extract.
+FRAME_PARAMS="filter=$FRAME_FILTER format=$FRAME_FORMAT scale=$SCALE_KEY"
+if [[ ${#FFMPEG_ARGS[@]} -gt 0 ]]; then
+  FRAME_PARAMS="$FRAME_PARAMS extra=${FFMPEG_ARGS[*]}"
+fi
+PARAMS_FILE="$FRAMES_DIR/params.txt"
+FRAME_COUNT=$(find "$FRAMES_DIR" -name "*.$FRAME_FORMAT" 2>/dev/null | wc -l | tr -d ' ')
+if [[ "$FRAME_COUNT" -gt 0 && -f "$PARAMS_FILE" && "$(cat "$PARAMS_FILE")" == "$FRAME_PARAMS" ]]; then
   echo "[3/5] Frames cached: $FRAME_COUNT frames in $FRAMES_DIR"
 else
+  if [[ -n "$(find "$FRAMES_DIR" -name 'frame_*' 2>/dev/null | head -1)" ]]; then
+    echo "  cached frames are incomplete or used different settings, re-extracting"
+    find "$FRAMES_DIR" -name 'frame_*' -delete
+  fi
+  rm -f "$PARAMS_FILE" "$FRAMES_DIR/timestamps.txt"
   echo "[3/5] Extracting frames (scene detection + interval fallback)..."
-  # Extract frames and capture their actual timestamps
-  ffmpeg -i "$VIDEO_PATH" \
-    -vf "select='gt(scene,0.2)+not(mod(n,25*$INTERVAL))',showinfo,scale=1024:-1" \
-    -vsync vfr -q:v 2 \
-    "$FRAMES_DIR/frame_%04d.jpg" 2>&1 | \
-    grep "pts_time:" | sed 's/.*pts_time:\([0-9.]*\).*/\1/' > "$FRAMES_DIR/timestamps.txt"
-  FRAME_COUNT=$(find "$FRAMES_DIR" -name "*.jpg" | wc -l | tr -d ' ')
+  # Extract frames; the full ffmpeg log is kept for diagnostics and the
+  # showinfo lines in it (info level, hence no lower -loglevel) give the
+  # actual frame timestamps
+  FFMPEG_LOG="$FRAMES_DIR/ffmpeg.log"
+  PROGRESS_FILE="$FRAMES_DIR/progress.txt"
+  # --ffmpeg-arg values go before the wrapper's own output options, which
+  # therefore win if both set the same one
+  ffmpeg -nostdin "${INPUT_ARGS[@]+"${INPUT_ARGS[@]}"}" -i "$VIDEO_PATH" \
+    ${FFMPEG_ARGS[@]+"${FFMPEG_ARGS[@]}"} -vf "$FRAME_FILTER,showinfo,scale=$SCALE" \
+    -vsync vfr "${QUALITY_ARGS[@]+"${QUALITY_ARGS[@]}"}" -progress "$PROGRESS_FILE" \
+    "$FRAMES_DIR/frame_%04d.$FRAME_FORMAT" 2>"$FFMPEG_LOG" &
+  FFMPEG_PID=$!
+  watch_ffmpeg "$FFMPEG_PID" "$FFMPEG_LOG" "$PROGRESS_FILE"
+  if ! wait "$FFMPEG_PID"; then
+    echo "error: frame extraction failed:" >&2
+    grep -v "pts_time:" "$FFMPEG_LOG" | tail -5 | sed 's/^/  /' >&2
+    echo "  full log: $FFMPEG_LOG" >&2
+    exit 1
+  fi
+  # Seeking resets timestamps to 0; shift them back to video time so slides
+  # line up with the (full-video) transcript and the report's times
+  { grep "pts_time:" "$FFMPEG_LOG" || true; } | sed 's/.*pts_time:\([0-9.]*\).*/\1/' |
+    awk -v off="${FROM:-0}" 'off > 0 { printf "%.6f\n", $1 + off; next } { print }' > "$FRAMES_DIR/timestamps.txt"
+  FRAME_COUNT=$(find "$FRAMES_DIR" -name "*.$FRAME_FORMAT" | wc -l | tr -d ' ')
+  echo "$FRAME_PARAMS" > "$PARAMS_FILE"
   echo "  extracted $FRAME_COUNT frames"
 fi
 
+# Subtitles (optional): YouTube's own or auto-generated captions, used as the
+# transcript when available so no whisper pass is needed
+TRANSCRIPT_ARGS=()
+HAVE_SUBS=0
+if [[ "$SUBTITLES" -eq 1 ]]; then
+  SUBS_PATH=$(find "$VIDEOS_DIR" -name "$VIDEO_ID.*.vtt" 2>/dev/null | sort | head -1)
+  if [[ -n "$SUBS_PATH" ]]; then
+    echo "[4/5] Subtitles cached: $SUBS_PATH"
+  else
+    echo "[4/5] Downloading subtitles ($SUB_LANGS)..."
+    yt-dlp ${YTDLP_ARGS[@]+"${YTDLP_ARGS[@]}"} --skip-download --write-subs --write-auto-subs --sub-langs "$SUB_LANGS" \
+      --sub-format vtt -o "$VIDEOS_DIR/$VIDEO_ID.%(ext)s" "$URL" >/dev/null 2>&1 || true
+    SUBS_PATH=$(find "$VIDEOS_DIR" -name "$VIDEO_ID.*.vtt" 2>/dev/null | sort | head -1)
+  fi
+  if [[ -n "$SUBS_PATH" ]]; then
+    TRANSCRIPT_ARGS=("--subtitles" "$SUBS_PATH")
+    HAVE_SUBS=1
+  else
+    echo "  no subtitles available, falling back to transcription"
+  fi
+fi
+
 # Transcribe audio (optional, skip if whisper not available)
 TRANSCRIPT_PATH="$VIDEOS_DIR/$VIDEO_ID.json"
-TRANSCRIPT_ARGS=()
-if [[ -f "$TRANSCRIPT_PATH" ]]; then
+if [[ "$HAVE_SUBS" -eq 1 ]]; then
+  true
+elif [[ -f "$TRANSCRIPT_PATH" ]]; then
   echo "[4/5] Transcript cached: $TRANSCRIPT_PATH"
   TRANSCRIPT_ARGS=("--transcript" "$TRANSCRIPT_PATH")
+elif [[ -n "$SAMPLE" ]]; then
+  echo "[4/5] Sample run, skipping transcription"
 else
   AUDIO_PATH="$VIDEOS_DIR/$VIDEO_ID.wav"
   if [[ ! -f "$AUDIO_PATH" ]]; then
@@ -125,11 +582,25 @@ VIDEO_OUTPUT="$OUTPUT/$OUTPUT_NAME"
 echo "[5/5] Extracting slides..."
 yt-sl --frames "$FRAMES_DIR" \
   --output "$VIDEO_OUTPUT" \
-  --title "$TITLE" \
+  --title "$REPORT_TITLE" \
   --url "$URL" \
   --interval "$INTERVAL" \
+  --threshold "$THRESHOLD" \
+  "${META_ARGS[@]+"${META_ARGS[@]}"}" \
   "${TRANSCRIPT_ARGS[@]+"${TRANSCRIPT_ARGS[@]}"}" \
   "${EXTRA_ARGS[@]+"${EXTRA_ARGS[@]}"}"
 
+# Keep the source video next to the report (hard link when on the same
+# filesystem, so the cached copy costs no extra space)
+if [[ "$SAVE_VIDEO" -eq 1 ]]; then
+  SAVED_VIDEO="$VIDEO_OUTPUT/$OUTPUT_NAME.mp4"
+  rm -f "$SAVED_VIDEO"
+  ln "$VIDEO_PATH" "$SAVED_VIDEO" 2>/dev/null || cp "$VIDEO_PATH" "$SAVED_VIDEO"
+  echo "  video saved: $SAVED_VIDEO"
+  echo "  output size incl. video: $(du -sk "$VIDEO_OUTPUT" | awk '{ printf "%.1f MB", $1 * 1024 / 1e6 }')"
+fi
+
+# The binary's "[4/4] Report:" line names the documents it wrote (they
+# follow any --report-name/--format passed through)
 echo ""
-echo "Done: $VIDEO_OUTPUT/report.md"
+echo "Done: $VIDEO_OUTPUT"